@@ -0,0 +1,4 @@
+-- SQLITE3
+
+-- Player IPs can now be IPv4 or IPv6
+ALTER TABLE minecraft_users RENAME COLUMN player_ipv4 TO player_ip;
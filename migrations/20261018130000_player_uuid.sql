@@ -0,0 +1,31 @@
+-- SQLITE3
+
+-- Minecraft accounts are identified by their UUID (Mojang or offline) when the
+-- proxy reports it; name + IP only identifies accounts from older proxies.
+-- SQLite cannot drop a table constraint in place, so the table is rebuilt.
+-- Pending registrations are short-lived and are dropped with the old table.
+CREATE TABLE minecraft_users_new (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Minecraft User ID (Snowflake ID)
+  discord_user_id INTEGER, -- User ID (Snowflake ID)
+  player_uuid TEXT UNIQUE, -- Minecraft player UUID (NULL for older proxies)
+  player_name TEXT NOT NULL, -- Minecraft username
+  player_ip TEXT NOT NULL, -- Minecraft player IP address (last seen)
+  FOREIGN KEY (discord_user_id) REFERENCES discord_users(id) ON DELETE CASCADE
+);
+
+INSERT INTO minecraft_users_new (id, discord_user_id, player_name, player_ip)
+SELECT id, discord_user_id, player_name, player_ip FROM minecraft_users;
+
+DROP TABLE minecraft_users;
+ALTER TABLE minecraft_users_new RENAME TO minecraft_users;
+
+-- Accounts without a UUID are still identified by name + IP
+CREATE UNIQUE INDEX IF NOT EXISTS minecraft_users_legacy_identity
+ON minecraft_users(player_name, player_ip)
+WHERE player_uuid IS NULL;
+
+-- IP address the registration was started from
+ALTER TABLE minecraft_registrations ADD COLUMN player_ip TEXT;
+
+-- Whether the player's IP is checked as a secondary factor on this proxy
+ALTER TABLE minecraft_proxies ADD COLUMN require_ip_match BOOLEAN NOT NULL DEFAULT FALSE;
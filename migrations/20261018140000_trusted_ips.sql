@@ -0,0 +1,14 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_trusted_ips (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Trusted IP ID (Snowflake ID)
+  minecraft_user_id INTEGER NOT NULL, -- Minecraft User ID (Snowflake ID)
+  player_ip TEXT NOT NULL, -- Minecraft player IP address
+  status INTEGER NOT NULL, -- Approval status (enum as integer)
+  FOREIGN KEY (minecraft_user_id) REFERENCES minecraft_users(id) ON DELETE CASCADE,
+  UNIQUE(minecraft_user_id, player_ip)
+);
+
+-- The IP each linked account was linked from is trusted (reusing the account snowflake)
+INSERT OR IGNORE INTO minecraft_trusted_ips (id, minecraft_user_id, player_ip, status)
+SELECT id, id, player_ip, 1 FROM minecraft_users WHERE discord_user_id IS NOT NULL;
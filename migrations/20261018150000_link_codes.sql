@@ -0,0 +1,8 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS discord_link_codes (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Link code ID (Snowflake ID)
+  code TEXT NOT NULL UNIQUE, -- One-time code typed in-game
+  discord_user_id INTEGER NOT NULL UNIQUE, -- Discord User ID (Snowflake ID)
+  FOREIGN KEY (discord_user_id) REFERENCES discord_users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+-- SQLITE3
+
+-- Code typed with /minecraft verify on Discord (NULL for OAuth registrations)
+ALTER TABLE minecraft_registrations ADD COLUMN verification_code TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS minecraft_registrations_verification_code
+ON minecraft_registrations(verification_code);
+
+-- Registration mode of the proxy (enum as integer, NULL = global default)
+ALTER TABLE minecraft_proxies ADD COLUMN registration_mode INTEGER;
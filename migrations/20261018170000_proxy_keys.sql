@@ -0,0 +1,8 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_proxy_keys (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Proxy key ID (Snowflake ID)
+  proxy_id TEXT NOT NULL UNIQUE, -- Proxy ID (UUID)
+  key_hash TEXT NOT NULL UNIQUE, -- SHA-256 of the API key (hex)
+  FOREIGN KEY (proxy_id) REFERENCES minecraft_proxies(id) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+-- SQLITE3
+
+-- One-time code printed by an unclaimed proxy, redeemed with /minecraft claim (NULL once claimed)
+ALTER TABLE minecraft_proxies ADD COLUMN claim_code TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS minecraft_proxies_claim_code
+ON minecraft_proxies(claim_code);
+
+-- Proxies are now bound to a guild by /minecraft claim and released by /minecraft proxy unclaim,
+-- unassigning servers must no longer release them
+DROP TRIGGER IF EXISTS nullify_proxy_guild_on_server_update;
+DROP TRIGGER IF EXISTS nullify_proxy_guild_on_server_delete;
@@ -0,0 +1,8 @@
+-- SQLITE3
+
+-- Role allowed to run the guild's Minecraft management commands (NULL = Manage Server only)
+ALTER TABLE discord_guilds ADD COLUMN admin_role_id INTEGER;
+
+-- Guilds now hold settings, keep them when their last proxy is released or deleted
+DROP TRIGGER IF EXISTS delete_orphaned_guild_on_proxy_update;
+DROP TRIGGER IF EXISTS delete_orphaned_guild_on_proxy_delete;
@@ -0,0 +1,17 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_server_roles (
+  server_id INTEGER NOT NULL, -- Server ID (Snowflake ID)
+  discord_role_id INTEGER NOT NULL, -- Discord Role ID (Snowflake ID)
+  FOREIGN KEY (server_id) REFERENCES minecraft_servers(id) ON DELETE CASCADE,
+  PRIMARY KEY (server_id, discord_role_id)
+);
+
+-- How the roles of a game server are matched (enum as integer, 0 = any role)
+ALTER TABLE minecraft_servers ADD COLUMN role_match_mode INTEGER NOT NULL DEFAULT 0;
+
+-- Move the single role of each server into the join table
+INSERT OR IGNORE INTO minecraft_server_roles (server_id, discord_role_id)
+SELECT id, discord_role_id FROM minecraft_servers WHERE discord_role_id IS NOT NULL;
+
+ALTER TABLE minecraft_servers DROP COLUMN discord_role_id;
@@ -0,0 +1,12 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_server_denials (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Denial ID (Snowflake ID)
+  server_id INTEGER NOT NULL, -- Server ID (Snowflake ID)
+  discord_user_id INTEGER, -- Denied Discord User ID (Snowflake ID, NULL for role denials)
+  discord_role_id INTEGER, -- Denied Discord Role ID (Snowflake ID, NULL for user denials)
+  FOREIGN KEY (server_id) REFERENCES minecraft_servers(id) ON DELETE CASCADE,
+  CHECK ((discord_user_id IS NULL) != (discord_role_id IS NULL)),
+  UNIQUE(server_id, discord_user_id),
+  UNIQUE(server_id, discord_role_id)
+);
@@ -0,0 +1,13 @@
+-- SQLITE3
+
+-- What to do when Discord can't be reached to check a member (enum as integer, 0 = fail closed)
+ALTER TABLE minecraft_servers ADD COLUMN fail_policy INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS minecraft_last_allowed (
+  server_id INTEGER NOT NULL, -- Server ID (Snowflake ID)
+  discord_user_id INTEGER NOT NULL, -- Discord User ID (Snowflake ID)
+  allowed_at INTEGER NOT NULL, -- Last time the member was allowed (Unix timestamp in milliseconds)
+  FOREIGN KEY (server_id) REFERENCES minecraft_servers(id) ON DELETE CASCADE,
+  FOREIGN KEY (discord_user_id) REFERENCES discord_users(id) ON DELETE CASCADE,
+  PRIMARY KEY (server_id, discord_user_id)
+);
@@ -0,0 +1,15 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_access_grants (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Grant ID (Snowflake ID)
+  server_id INTEGER NOT NULL, -- Server ID (Snowflake ID)
+  discord_user_id INTEGER NOT NULL, -- Granted Discord User ID (Snowflake ID)
+  granted_by INTEGER NOT NULL, -- Discord User ID of the admin who granted the access (Snowflake ID)
+  expires_at INTEGER NOT NULL, -- Expiration time of the grant (Unix timestamp in milliseconds)
+  FOREIGN KEY (server_id) REFERENCES minecraft_servers(id) ON DELETE CASCADE,
+  UNIQUE(server_id, discord_user_id)
+);
+
+-- The scheduler looks up expired grants
+CREATE INDEX IF NOT EXISTS minecraft_access_grants_expires_at
+ON minecraft_access_grants(expires_at);
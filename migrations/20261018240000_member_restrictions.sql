@@ -0,0 +1,7 @@
+-- SQLITE3
+
+-- Deny game access to members timed out on Discord
+ALTER TABLE discord_guilds ADD COLUMN deny_timed_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Deny game access to members that haven't passed membership screening yet
+ALTER TABLE discord_guilds ADD COLUMN deny_pending BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,7 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_events (
+  sequence INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL, -- Event sequence number (monotonically increasing)
+  event BLOB NOT NULL, -- Protobuf-encoded ServerEvent
+  created_at INTEGER NOT NULL -- Creation time of the event (Unix timestamp in milliseconds)
+);
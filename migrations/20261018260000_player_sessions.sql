@@ -0,0 +1,20 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS player_sessions (
+  id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Session ID (Snowflake ID)
+  minecraft_user_id INTEGER NOT NULL, -- Minecraft User ID (Snowflake ID)
+  server_id INTEGER NOT NULL, -- Server ID (Snowflake ID)
+  started_at INTEGER NOT NULL, -- Time the player joined the server (Unix timestamp in milliseconds)
+  ended_at INTEGER, -- Time the player left the server (Unix timestamp in milliseconds, NULL = still online)
+  FOREIGN KEY (minecraft_user_id) REFERENCES minecraft_users(id) ON DELETE CASCADE,
+  FOREIGN KEY (server_id) REFERENCES minecraft_servers(id) ON DELETE CASCADE
+);
+
+-- Online players are the sessions that haven't ended
+CREATE INDEX IF NOT EXISTS player_sessions_online
+ON player_sessions(server_id)
+WHERE ended_at IS NULL;
+
+-- Playtime is aggregated per player
+CREATE INDEX IF NOT EXISTS player_sessions_minecraft_user
+ON player_sessions(minecraft_user_id, started_at);
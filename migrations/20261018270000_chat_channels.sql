@@ -0,0 +1,10 @@
+-- SQLITE3
+
+CREATE TABLE IF NOT EXISTS minecraft_chat_channels (
+  server_id INTEGER PRIMARY KEY UNIQUE NOT NULL, -- Relayed server ID (Snowflake ID)
+  discord_channel_id INTEGER UNIQUE NOT NULL, -- Discord text channel ID relaying the server's chat (Snowflake ID)
+  webhook_id INTEGER NOT NULL, -- Discord webhook ID posting the players' messages (Snowflake ID)
+  webhook_token TEXT NOT NULL, -- Discord webhook token (string)
+  bound_by INTEGER NOT NULL, -- Discord User ID of the admin who bound the channel (Snowflake ID)
+  FOREIGN KEY (server_id) REFERENCES minecraft_servers(id) ON DELETE CASCADE
+);
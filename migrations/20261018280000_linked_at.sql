@@ -0,0 +1,7 @@
+-- SQLITE3
+
+-- When the Minecraft user was linked to its Discord user (Unix timestamp in milliseconds)
+ALTER TABLE minecraft_users ADD COLUMN linked_at INTEGER;
+
+-- Accounts linked before this column existed show when they were first seen
+UPDATE minecraft_users SET linked_at = id >> 22 WHERE discord_user_id IS NOT NULL;
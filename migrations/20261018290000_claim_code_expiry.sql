@@ -0,0 +1,7 @@
+-- SQLITE3
+
+-- When the claim code was issued (Unix timestamp in milliseconds), codes expire after a day
+ALTER TABLE minecraft_proxies ADD COLUMN claim_code_issued_at INTEGER;
+
+-- Codes issued before expiry existed can't be dated, the next registration issues a new one
+UPDATE minecraft_proxies SET claim_code = NULL WHERE claim_code IS NOT NULL;
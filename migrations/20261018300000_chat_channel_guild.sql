@@ -0,0 +1,11 @@
+-- SQLITE3
+
+-- Discord Guild ID the chat channel belongs to (Snowflake ID), relaying stops once the proxy changes owner
+ALTER TABLE minecraft_chat_channels ADD COLUMN discord_guild_id INTEGER;
+
+UPDATE minecraft_chat_channels SET discord_guild_id = (
+  SELECT minecraft_proxies.discord_guild_id
+  FROM minecraft_servers
+  JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+  WHERE minecraft_servers.id = minecraft_chat_channels.server_id
+);
@@ -0,0 +1,4 @@
+-- SQLITE3
+
+-- Whether the proxy still lists the server, servers it drops are kept with their history and settings
+ALTER TABLE minecraft_servers ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE;
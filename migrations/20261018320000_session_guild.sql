@@ -0,0 +1,15 @@
+-- SQLITE3
+
+-- Discord Guild ID owning the proxy when the session started (Snowflake ID, NULL = unclaimed proxy)
+ALTER TABLE player_sessions ADD COLUMN discord_guild_id INTEGER;
+
+UPDATE player_sessions SET discord_guild_id = (
+  SELECT minecraft_proxies.discord_guild_id
+  FROM minecraft_servers
+  JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+  WHERE minecraft_servers.id = player_sessions.server_id
+);
+
+-- Playtime and leaderboards only count the sessions played while the guild owned the proxy
+CREATE INDEX IF NOT EXISTS player_sessions_guild
+ON player_sessions(discord_guild_id, started_at);
@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use poise::CreateReply;
+use tracing::info;
+
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::{
+    Context, Error,
+    utils::{embed, linking, snowflake},
+};
+
+async fn autocomplete_account(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let mut player_names = Vec::new();
+
+    let user_id = ctx.author().id.get() as i64;
+    let pattern = format!("%{}%", partial);
+
+    if let Ok(rows) = sqlx::query!(
+        "SELECT DISTINCT player_name FROM minecraft_users
+        WHERE discord_user_id = ?1 AND player_name LIKE ?2",
+        user_id,
+        pattern
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    {
+        for row in rows {
+            player_names.push(row.player_name);
+        }
+    }
+
+    info!(
+        "[autocomplete_account] ({}): {:?}",
+        player_names.len(),
+        player_names
+    );
+
+    player_names
+}
+
+/// Manage your linked Minecraft accounts
+#[poise::command(
+    slash_command,
+    subcommands("list", "unlink", "link"),
+    subcommand_required = true
+)]
+pub async fn account(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List the Minecraft accounts linked to your Discord account
+#[poise::command(slash_command)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user_id = ctx.author().id.get() as i64;
+    let records = sqlx::query!(
+        "SELECT player_name, player_ip, linked_at FROM minecraft_users
+        WHERE discord_user_id = ?1 ORDER BY player_name",
+        user_id
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    let embed = if records.is_empty() {
+        embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft accounts")
+            .description("You have no linked Minecraft accounts.")
+    } else {
+        embed::success()
+            .title("<:minecraft:1435794853517721722>  Minecraft accounts")
+            .fields(records.into_iter().map(|record| {
+                (
+                    record.player_name,
+                    match record.linked_at {
+                        Some(linked_at) => {
+                            format!("🌐 `{}`\n🔗 <t:{}:R>", record.player_ip, linked_at / 1000)
+                        }
+                        None => format!("🌐 `{}`", record.player_ip),
+                    },
+                    true,
+                )
+            }))
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Unlink a Minecraft account from your Discord account
+#[poise::command(slash_command)]
+pub async fn unlink(
+    ctx: Context<'_>,
+
+    #[description = "Minecraft account to unlink"]
+    #[autocomplete = "autocomplete_account"]
+    account: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user_id = ctx.author().id.get() as i64;
+    let records = sqlx::query!(
+        "SELECT id, player_uuid, player_name, player_ip FROM minecraft_users
+        WHERE discord_user_id = ?1 AND player_name = ?2",
+        user_id,
+        account
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    if records.is_empty() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft account unlink")
+            .description("The specified Minecraft account isn't linked to you.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    for record in records {
+        info!(
+            "[minecraft account unlink] Unlinking Minecraft user {} from {}",
+            record.id, user_id
+        );
+
+        // Only detach the account, its playtime and trusted IPs stay with the player
+        sqlx::query!(
+            "UPDATE minecraft_users SET discord_user_id = NULL, linked_at = NULL WHERE id = ?1",
+            record.id
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        // Let the proxies kick the session
+        minecraft_bridge::disconnect::player_update(
+            Arc::new(ctx.data().clone()),
+            EventTarget::All,
+            record.player_uuid,
+            record.player_name,
+            record.player_ip,
+        )
+        .await;
+    }
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft account unlink")
+        .description("Minecraft account successfully unlinked.");
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Generate a one-time code to link a Minecraft account from in-game
+#[poise::command(slash_command)]
+pub async fn link(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user_id = ctx.author().id.get() as i64;
+    let id = snowflake::generate_id();
+    let code = linking::generate_code();
+
+    // Insert Discord user if not exists
+    sqlx::query!(
+        "INSERT OR IGNORE INTO discord_users (id) VALUES (?1)",
+        user_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    // Replace any previous code of this user
+    sqlx::query!(
+        "INSERT OR REPLACE INTO discord_link_codes (id, code, discord_user_id) VALUES (?1, ?2, ?3)",
+        id,
+        code,
+        user_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    let expires_at = (snowflake::snowflake_to_timestamp(id) + linking::CODE_EXPIRY_MS) / 1000;
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft account link")
+        .description(format!(
+            "Join the Minecraft server and type `/twig link {}`.\n\nThis code expires <t:{}:R>.",
+            code, expires_at
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
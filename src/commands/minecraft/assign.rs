@@ -1,55 +1,15 @@
 use poise::{CreateReply, serenity_prelude::Role};
 use tracing::info;
 
-use crate::utils::minecraft::MinecraftServerType;
+use super::autocomplete_server;
+use crate::utils::minecraft::{MinecraftServerType, RoleMatchMode};
 use crate::{
     Context, Error,
     utils::{checks, embed},
 };
 
-async fn autocomplete_server(ctx: Context<'_>, partial: &str) -> Vec<String> {
-    let mut server_ids = Vec::new();
-
-    let guild_id = match ctx.guild_id() {
-        Some(id) => u64::from(id),
-        None => return server_ids,
-    };
-
-    let guild_id_i64 = guild_id as i64;
-    let pattern = format!("%{}%", partial);
-
-    match sqlx::query!(
-        "SELECT minecraft_servers.server_name 
-        FROM minecraft_servers 
-        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id 
-        WHERE
-            (minecraft_proxies.discord_guild_id = ?1 OR minecraft_proxies.discord_guild_id IS NULL) AND
-            minecraft_servers.server_name LIKE ?2",
-        guild_id_i64,
-        pattern
-    )
-    .fetch_all(&ctx.data().db)
-    .await
-    {
-        Ok(rows) => {
-            for row in rows {
-                server_ids.push(row.server_name);
-            }
-        }
-        Err(_) => {}
-    }
-
-    info!(
-        "[autocomplete_server] ({}): {:?}",
-        server_ids.len(),
-        server_ids
-    );
-
-    server_ids
-}
-
-/// Assign a Discord role to a Minecraft server
-#[poise::command(slash_command, guild_only = true, check = "checks::is_owner")]
+/// Assign Discord roles to a Minecraft server
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
 pub async fn assign(
     ctx: Context<'_>,
 
@@ -57,7 +17,9 @@ pub async fn assign(
     #[autocomplete = "autocomplete_server"]
     server: String,
 
-    #[description = "Role to assign to the server"] role: Option<Role>,
+    #[description = "Role to add to the server (empty = lobby server)"] role: Option<Role>,
+
+    #[description = "Whether players need any or all roles"] mode: Option<RoleMatchMode>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
@@ -77,21 +39,23 @@ pub async fn assign(
 
     // Check if server exists and belongs to guild
     let guild_id_i64 = guild_id as i64;
-    let Some((server_id, proxy_id)) = sqlx::query!(
-        "SELECT minecraft_servers.id, minecraft_servers.proxy_id FROM minecraft_servers
+    let Some(server_id) = sqlx::query!(
+        "SELECT minecraft_servers.id FROM minecraft_servers
         JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
         WHERE
-            (minecraft_proxies.discord_guild_id = ?1 OR minecraft_proxies.discord_guild_id IS NULL) AND
+            minecraft_proxies.discord_guild_id = ?1 AND
             minecraft_servers.server_name = ?2",
         guild_id_i64,
         server
     )
     .fetch_optional(&ctx.data().db)
     .await?
-    .map(|record| (record.id, record.proxy_id)) else {
+    .map(|record| record.id) else {
         let embed = embed::warn()
             .title("<:minecraft:1435794853517721722>  /minecraft assign server")
-            .description("The specified server doesn't exist or isn't available at this guild.");
+            .description(
+                "The specified server doesn't exist or its proxy isn't claimed by this guild (see `/minecraft claim`).",
+            );
 
         ctx.send(CreateReply::default().embed(embed).ephemeral(true))
             .await?;
@@ -99,48 +63,61 @@ pub async fn assign(
         return Ok(());
     };
 
-    // Insert guild if not exists
-    sqlx::query!(
-        "INSERT OR IGNORE INTO discord_guilds (id) VALUES (?1)",
-        guild_id_i64
-    )
-    .execute(&ctx.data().db)
-    .await?;
-
-    // Update proxy guild_id
-    sqlx::query!(
-        "UPDATE minecraft_proxies SET discord_guild_id = ?1 WHERE id = ?2",
-        guild_id_i64,
-        proxy_id
-    )
-    .execute(&ctx.data().db)
-    .await?;
+    let mode_i64 = mode.map(|mode| mode as i64);
 
-    // Update server with role
-    if role.as_ref().is_some() {
+    if let Some(role) = role.as_ref() {
         info!(
             "[minecraft assign] Assigning role {} to server {}",
-            role.as_ref().unwrap().id,
-            server_id
+            role.id, server_id
         );
 
-        let role_id_i64 = u64::from(role.as_ref().unwrap().id) as i64;
+        // Add the role to the server's role set
+        let role_id_i64 = u64::from(role.id) as i64;
         sqlx::query!(
-            "UPDATE minecraft_servers SET discord_role_id = ?1, server_type = ?2
+            "INSERT OR IGNORE INTO minecraft_server_roles (server_id, discord_role_id) VALUES (?1, ?2)",
+            server_id,
+            role_id_i64
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE minecraft_servers SET server_type = ?1, role_match_mode = COALESCE(?2, role_match_mode)
          WHERE id = ?3",
-            role_id_i64,
             MinecraftServerType::Game as i32,
+            mode_i64,
             server_id
         )
         .execute(&ctx.data().db)
         .await?;
+    } else if mode.is_some() {
+        info!(
+            "[minecraft assign] Setting role match mode {:?} on server {}",
+            mode, server_id
+        );
 
-        let embed = embed::success()
-            .title("<:minecraft:1435794853517721722>  Minecraft assign server")
-            .description("Role successfully assigned to the specified server.");
+        let game = MinecraftServerType::Game as i32;
+        let result = sqlx::query!(
+            "UPDATE minecraft_servers SET role_match_mode = ?1 WHERE id = ?2 AND server_type = ?3",
+            mode_i64,
+            server_id,
+            game
+        )
+        .execute(&ctx.data().db)
+        .await?;
 
-        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
-            .await?;
+        if result.rows_affected() == 0 {
+            let embed = embed::warn()
+                .title("<:minecraft:1435794853517721722>  Minecraft assign server")
+                .description(
+                    "Assign a role to the specified server before choosing its match mode.",
+                );
+
+            ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+                .await?;
+
+            return Ok(());
+        }
     } else {
         info!(
             "[minecraft assign] Assigning guild {} to lobby server {}",
@@ -148,7 +125,14 @@ pub async fn assign(
         );
 
         sqlx::query!(
-            "UPDATE minecraft_servers SET server_type = ?1, discord_role_id = NULL
+            "DELETE FROM minecraft_server_roles WHERE server_id = ?1",
+            server_id
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE minecraft_servers SET server_type = ?1
          WHERE id = ?2",
             MinecraftServerType::Lobby as i32,
             server_id
@@ -162,7 +146,46 @@ pub async fn assign(
 
         ctx.send(CreateReply::default().embed(embed).ephemeral(true))
             .await?;
+
+        return Ok(());
     }
 
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft assign server")
+        .description(server_roles_description(ctx, server_id).await?);
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
     Ok(())
 }
+
+/// Describes the roles a player needs to join a game server
+async fn server_roles_description(ctx: Context<'_>, server_id: i64) -> Result<String, Error> {
+    let record = sqlx::query!(
+        "SELECT role_match_mode FROM minecraft_servers WHERE id = ?1",
+        server_id
+    )
+    .fetch_one(&ctx.data().db)
+    .await?;
+
+    let roles = sqlx::query!(
+        "SELECT discord_role_id FROM minecraft_server_roles WHERE server_id = ?1",
+        server_id
+    )
+    .fetch_all(&ctx.data().db)
+    .await?
+    .into_iter()
+    .map(|record| format!("<@&{}>", record.discord_role_id))
+    .collect::<Vec<String>>();
+
+    let separator = match RoleMatchMode::from_i64(record.role_match_mode) {
+        Some(RoleMatchMode::All) => " **and** ",
+        _ => " **or** ",
+    };
+
+    Ok(format!(
+        "Roles successfully updated. Players now need {} to join the specified server.",
+        roles.join(separator)
+    ))
+}
@@ -0,0 +1,245 @@
+use std::sync::Arc;
+
+use poise::CreateReply;
+use poise::serenity_prelude::{Role, RoleId, User, UserId};
+use tracing::info;
+
+use super::autocomplete_server;
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::{
+    Context, Error,
+    utils::{checks, embed, snowflake},
+};
+
+/// Deny a Discord user or role from joining a Minecraft server
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn ban(
+    ctx: Context<'_>,
+
+    #[description = "Server to deny access to"]
+    #[autocomplete = "autocomplete_server"]
+    server: String,
+
+    #[description = "Discord user to deny"] user: Option<User>,
+
+    #[description = "Discord role to deny"] role: Option<Role>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let Some((server_id, target)) = resolve(ctx, "Minecraft ban", &server, user, role).await?
+    else {
+        return Ok(());
+    };
+
+    let id = snowflake::generate_id();
+    let (user_id, role_id) = target.ids();
+    sqlx::query!(
+        "INSERT OR IGNORE INTO minecraft_server_denials (id, server_id, discord_user_id, discord_role_id)
+        VALUES (?1, ?2, ?3, ?4)",
+        id,
+        server_id,
+        user_id,
+        role_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    // Don't let the fail policy allow the denied members while Discord is unreachable
+    sqlx::query!(
+        "DELETE FROM minecraft_last_allowed WHERE server_id = ?1 AND (?2 IS NULL OR discord_user_id = ?2)",
+        server_id,
+        user_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[minecraft ban] Denied {} on server {}",
+        target.mention(),
+        server_id
+    );
+
+    recheck_players(ctx, &target).await?;
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft ban")
+        .description(format!(
+            "{} can no longer join `{}`.",
+            target.mention(),
+            server
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Lift a denial of a Discord user or role on a Minecraft server
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn unban(
+    ctx: Context<'_>,
+
+    #[description = "Server to allow access to again"]
+    #[autocomplete = "autocomplete_server"]
+    server: String,
+
+    #[description = "Discord user to allow again"] user: Option<User>,
+
+    #[description = "Discord role to allow again"] role: Option<Role>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let Some((server_id, target)) = resolve(ctx, "Minecraft unban", &server, user, role).await?
+    else {
+        return Ok(());
+    };
+
+    let (user_id, role_id) = target.ids();
+    let result = sqlx::query!(
+        "DELETE FROM minecraft_server_denials
+        WHERE server_id = ?1 AND (discord_user_id = ?2 OR discord_role_id = ?3)",
+        server_id,
+        user_id,
+        role_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft unban")
+            .description(format!(
+                "{} isn't denied on `{}`.",
+                target.mention(),
+                server
+            ));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    info!(
+        "[minecraft unban] Lifted the denial of {} on server {}",
+        target.mention(),
+        server_id
+    );
+
+    recheck_players(ctx, &target).await?;
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft unban")
+        .description(format!("{} can join `{}` again.", target.mention(), server));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Discord user or role targeted by a deny rule
+enum DenialTarget {
+    User(UserId),
+    Role(RoleId),
+}
+
+impl DenialTarget {
+    /// Returns the `(discord_user_id, discord_role_id)` columns of the rule
+    fn ids(&self) -> (Option<i64>, Option<i64>) {
+        match self {
+            DenialTarget::User(id) => (Some(id.get() as i64), None),
+            DenialTarget::Role(id) => (None, Some(id.get() as i64)),
+        }
+    }
+
+    fn mention(&self) -> String {
+        match self {
+            DenialTarget::User(id) => format!("<@{}>", id),
+            DenialTarget::Role(id) => format!("<@&{}>", id),
+        }
+    }
+}
+
+/// Resolves the server and the single user or role targeted by the command
+///
+/// Replies with a warning and returns `None` when the arguments are invalid.
+async fn resolve(
+    ctx: Context<'_>,
+    title: &str,
+    server: &str,
+    user: Option<User>,
+    role: Option<Role>,
+) -> Result<Option<(i64, DenialTarget)>, Error> {
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+
+    let target = match (user, role) {
+        (Some(user), None) => Some(DenialTarget::User(user.id)),
+        (None, Some(role)) if role.id.get() as i64 != guild_id => Some(DenialTarget::Role(role.id)),
+        _ => None,
+    };
+
+    let server_id = sqlx::query!(
+        "SELECT minecraft_servers.id FROM minecraft_servers
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_proxies.discord_guild_id = ?1 AND
+            minecraft_servers.server_name = ?2",
+        guild_id,
+        server
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    .map(|record| record.id);
+
+    let description = match (server_id, target) {
+        (Some(server_id), Some(target)) => return Ok(Some((server_id, target))),
+        (None, _) => "The specified server doesn't exist or isn't available at this guild.",
+        (_, None) => "Specify either a user or a role (other than @everyone).",
+    };
+
+    let embed = embed::warn()
+        .title(format!("<:minecraft:1435794853517721722>  {}", title))
+        .description(description);
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(None)
+}
+
+/// Asks the proxies to re-check the linked accounts affected by a deny rule
+///
+/// Role rules only affect the guild's cached members holding the role.
+async fn recheck_players(ctx: Context<'_>, target: &DenialTarget) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let user_ids = match target {
+        DenialTarget::User(user_id) => vec![*user_id],
+        DenialTarget::Role(role_id) => ctx
+            .data()
+            .member_cache
+            .find(guild_id, |member| member.roles.contains(role_id)),
+    };
+
+    for user_id in user_ids {
+        let discord_user_id = user_id.get() as i64;
+        let records = sqlx::query!(
+            "SELECT id FROM minecraft_users WHERE discord_user_id = ?1",
+            discord_user_id
+        )
+        .fetch_all(&ctx.data().db)
+        .await?;
+
+        for record in records {
+            minecraft_bridge::authenticated::broadcast_event(
+                Arc::new(ctx.data().clone()),
+                record.id,
+                EventTarget::Guild(guild_id),
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use poise::CreateReply;
+use tracing::info;
+
+use super::autocomplete_assigned_server;
+use crate::grpc::stream::minecraft_bridge;
+use crate::{
+    Context, Error,
+    utils::{checks, embed},
+};
+
+/// Send a message to the players of a Minecraft server
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn broadcast(
+    ctx: Context<'_>,
+
+    #[description = "Server to send the message to"]
+    #[autocomplete = "autocomplete_assigned_server"]
+    server: String,
+
+    #[description = "Message shown to the players"]
+    #[max_length = 256]
+    message: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let Some(record) = sqlx::query!(
+        "SELECT minecraft_servers.proxy_id
+        FROM minecraft_servers
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE minecraft_proxies.discord_guild_id = ?1 AND minecraft_servers.server_name = ?2",
+        guild_id,
+        server
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    else {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft broadcast")
+            .description(format!("Server `{}` not found in this guild.", server));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    };
+
+    if !ctx.data().proxy_sessions.is_connected(&record.proxy_id) {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft broadcast")
+            .description(format!("The proxy of `{}` isn't connected.", server));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    info!(
+        "[broadcast] {} broadcasted to server `{}`: {}",
+        ctx.author().tag(),
+        server,
+        message
+    );
+
+    let sender = ctx
+        .author_member()
+        .await
+        .map(|member| member.display_name().to_string())
+        .unwrap_or_else(|| ctx.author().name.clone());
+
+    let ack = minecraft_bridge::command::broadcast_message(
+        ctx.data(),
+        &record.proxy_id,
+        server.clone(),
+        message,
+        sender,
+    )
+    .await;
+
+    let embed = match ack {
+        Some(ack) if ack.success => embed::success().description(
+            ack.message
+                .unwrap_or_else(|| format!("Message sent to `{}`.", server)),
+        ),
+        Some(ack) => embed::warn().description(format!(
+            "The proxy couldn't send the message to `{}`: {}",
+            server,
+            ack.message.unwrap_or_else(|| "no reason given".to_string())
+        )),
+        None => embed::warn().description(format!(
+            "The proxy didn't acknowledge the message to `{}`, it may not support broadcasts.",
+            server
+        )),
+    }
+    .title("<:minecraft:1435794853517721722>  Minecraft broadcast");
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+use poise::CreateReply;
+use poise::serenity_prelude::{self as serenity, CreateWebhook, GuildChannel};
+use tracing::{info, warn};
+
+use super::autocomplete_assigned_server;
+use crate::{
+    Context, Error,
+    utils::{checks, config, embed},
+};
+
+/// Relay the chat of Minecraft servers to Discord channels
+#[poise::command(
+    slash_command,
+    guild_only = true,
+    subcommands("bind", "unbind"),
+    subcommand_required = true
+)]
+pub async fn chat(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Relay the chat of a Minecraft server to a Discord text channel, both ways
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn bind(
+    ctx: Context<'_>,
+
+    #[description = "Server whose chat is relayed"]
+    #[autocomplete = "autocomplete_assigned_server"]
+    server: String,
+
+    #[description = "Text channel to relay the chat to"]
+    #[channel_types("Text")]
+    channel: GuildChannel,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let Some(server_id) = server_id(ctx, guild_id, &server).await? else {
+        return Ok(());
+    };
+
+    let channel_id = channel.id.get() as i64;
+    if let Some(record) = sqlx::query!(
+        "SELECT minecraft_servers.server_name
+        FROM minecraft_chat_channels
+        JOIN minecraft_servers ON minecraft_chat_channels.server_id = minecraft_servers.id
+        WHERE minecraft_chat_channels.discord_channel_id = ?1 AND minecraft_chat_channels.server_id != ?2",
+        channel_id,
+        server_id
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft chat")
+            .description(format!(
+                "<#{}> already relays the chat of `{}`.",
+                channel.id, record.server_name
+            ));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    // The webhook posts the players' messages under their name and skin
+    let webhook = match channel
+        .id
+        .create_webhook(
+            ctx,
+            CreateWebhook::new(crate::utils::chat::sanitize_webhook_username(&format!(
+                "Minecraft {}",
+                server
+            ))),
+        )
+        .await
+    {
+        Ok(webhook) => webhook,
+        Err(e) => {
+            warn!(
+                "[chat::bind] Failed to create a webhook in channel {}: {}",
+                channel.id, e
+            );
+
+            let embed = embed::warn()
+                .title("<:minecraft:1435794853517721722>  Minecraft chat")
+                .description(format!(
+                    "Couldn't create a webhook in <#{}>, check that I have the `Manage Webhooks` permission there.",
+                    channel.id
+                ));
+
+            ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+                .await?;
+
+            return Ok(());
+        }
+    };
+
+    let webhook_id = webhook.id.get() as i64;
+    let webhook_token = webhook
+        .url()?
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    let bound_by = ctx.author().id.get() as i64;
+
+    // Rebinding a server replaces its previous channel
+    delete_webhook(ctx, server_id).await?;
+
+    sqlx::query!(
+        "INSERT INTO minecraft_chat_channels (server_id, discord_channel_id, webhook_id, webhook_token, bound_by, discord_guild_id)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+        ON CONFLICT(server_id) DO UPDATE SET
+            discord_channel_id = excluded.discord_channel_id,
+            webhook_id = excluded.webhook_id,
+            webhook_token = excluded.webhook_token,
+            bound_by = excluded.bound_by,
+            discord_guild_id = excluded.discord_guild_id",
+        server_id,
+        channel_id,
+        webhook_id,
+        webhook_token,
+        bound_by,
+        guild_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[chat::bind] {} bound the chat of server `{}` to channel {}",
+        ctx.author().tag(),
+        server,
+        channel.id
+    );
+
+    let description = match config::get_config().chat_relay {
+        true => format!(
+            "The chat of `{}` is now relayed to <#{}>, and messages sent there reach the server.",
+            server, channel.id
+        ),
+        false => format!(
+            "The chat of `{}` is now relayed to <#{}>. Messages sent there only reach the server once `CHAT_RELAY` is enabled.",
+            server, channel.id
+        ),
+    };
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft chat")
+        .description(description);
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Stop relaying the chat of a Minecraft server
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn unbind(
+    ctx: Context<'_>,
+
+    #[description = "Server whose chat is no longer relayed"]
+    #[autocomplete = "autocomplete_assigned_server"]
+    server: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let Some(server_id) = server_id(ctx, guild_id, &server).await? else {
+        return Ok(());
+    };
+
+    let unbound = delete_webhook(ctx, server_id).await?;
+
+    let embed = if unbound {
+        info!(
+            "[chat::unbind] {} unbound the chat of server `{}`",
+            ctx.author().tag(),
+            server
+        );
+
+        embed::success()
+            .title("<:minecraft:1435794853517721722>  Minecraft chat")
+            .description(format!("The chat of `{}` is no longer relayed.", server))
+    } else {
+        embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft chat")
+            .description(format!("The chat of `{}` isn't relayed.", server))
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Looks up a server of the guild, replying with a warning when it doesn't exist
+async fn server_id(ctx: Context<'_>, guild_id: i64, server: &str) -> Result<Option<i64>, Error> {
+    let record = sqlx::query!(
+        "SELECT minecraft_servers.id
+        FROM minecraft_servers
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE minecraft_proxies.discord_guild_id = ?1 AND minecraft_servers.server_name = ?2",
+        guild_id,
+        server
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?;
+
+    if record.is_none() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft chat")
+            .description(format!("Server `{}` not found in this guild.", server));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+    }
+
+    Ok(record.map(|record| record.id))
+}
+
+/// Removes the server's chat binding and its webhook, returns whether it had one
+pub(super) async fn delete_webhook(ctx: Context<'_>, server_id: i64) -> Result<bool, Error> {
+    let Some(record) = sqlx::query!(
+        "DELETE FROM minecraft_chat_channels WHERE server_id = ?1 RETURNING webhook_id, webhook_token",
+        server_id
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    else {
+        return Ok(false);
+    };
+
+    let webhook_id = serenity::WebhookId::new(record.webhook_id as u64);
+    if let Err(e) = ctx
+        .http()
+        .delete_webhook_with_token(
+            webhook_id,
+            &record.webhook_token,
+            Some("Minecraft chat unbound"),
+        )
+        .await
+    {
+        // Already deleted from Discord
+        warn!(
+            "[chat::delete_webhook] Failed to delete webhook {}: {}",
+            webhook_id, e
+        );
+    }
+
+    Ok(true)
+}
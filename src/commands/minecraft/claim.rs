@@ -0,0 +1,83 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use poise::CreateReply;
+use tracing::info;
+
+use crate::{
+    Context, Error,
+    utils::{checks, embed, linking},
+};
+
+/// Bind a proxy to this guild using the code printed on its console
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn claim(
+    ctx: Context<'_>,
+
+    #[description = "Claim code printed on the proxy console"] code: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let code = code.trim().to_uppercase();
+
+    // Codes older than the expiry may not have been purged yet
+    let issued_after = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+        - linking::CLAIM_CODE_EXPIRY_MS as i64;
+    let Some(proxy) = sqlx::query!(
+        "SELECT id FROM minecraft_proxies
+        WHERE claim_code = ?1 AND discord_guild_id IS NULL AND claim_code_issued_at > ?2",
+        code,
+        issued_after
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    else {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft claim proxy")
+            .description(
+                "The specified claim code is invalid, expired or was already used. Restart the proxy to get a new one.",
+            );
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    };
+
+    // Insert guild if not exists
+    sqlx::query!(
+        "INSERT OR IGNORE INTO discord_guilds (id) VALUES (?1)",
+        guild_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    // Claim codes are single-use
+    sqlx::query!(
+        "UPDATE minecraft_proxies SET discord_guild_id = ?1, claim_code = NULL, claim_code_issued_at = NULL WHERE id = ?2",
+        guild_id,
+        proxy.id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[minecraft claim] Proxy `{}` claimed by guild {}",
+        proxy.id, guild_id
+    );
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft claim proxy")
+        .description(format!(
+            "Proxy `{}` is now bound to this guild. Use `/minecraft assign` to configure its servers.",
+            proxy.id
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
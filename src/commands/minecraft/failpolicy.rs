@@ -0,0 +1,74 @@
+use poise::CreateReply;
+use tracing::info;
+
+use super::autocomplete_assigned_server;
+use crate::utils::minecraft::FailPolicy;
+use crate::{
+    Context, Error,
+    utils::{checks, config, embed},
+};
+
+/// Choose what a Minecraft server does when Discord is unreachable
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn failpolicy(
+    ctx: Context<'_>,
+
+    #[description = "Server to configure"]
+    #[autocomplete = "autocomplete_assigned_server"]
+    server: String,
+
+    #[description = "Policy applied while Discord is unreachable"] policy: FailPolicy,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let policy_i64 = policy as i64;
+    let result = sqlx::query!(
+        "UPDATE minecraft_servers SET fail_policy = ?1
+        WHERE server_name = ?2 AND server_type IS NOT NULL AND proxy_id IN (
+            SELECT id FROM minecraft_proxies WHERE discord_guild_id = ?3
+        )",
+        policy_i64,
+        server,
+        guild_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft fail policy")
+            .description("The specified server does not exist at this guild.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    info!(
+        "[minecraft failpolicy] Set fail_policy = {:?} on server `{}`",
+        policy, server
+    );
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft fail policy")
+        .description(match policy {
+            FailPolicy::Closed => {
+                "Players will be denied while Discord is unreachable.".to_string()
+            }
+            FailPolicy::LastKnownGood => format!(
+                "Players allowed in the last {} hours will keep access while Discord is unreachable.",
+                config::get_config().fail_open_grace_secs / 3600
+            ),
+            FailPolicy::LobbyOnly => {
+                "Linked players will be allowed while Discord is unreachable if this is a lobby server."
+                    .to_string()
+            }
+        });
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
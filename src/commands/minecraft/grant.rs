@@ -0,0 +1,131 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use poise::CreateReply;
+use poise::serenity_prelude::User;
+use tracing::info;
+
+use super::autocomplete_assigned_server;
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::{
+    Context, Error,
+    utils::{checks, duration, embed, snowflake},
+};
+
+/// Longest access a grant can give
+const MAX_GRANT_DURATION: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// Grant a Discord user temporary access to a Minecraft server, regardless of roles
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn grant(
+    ctx: Context<'_>,
+
+    #[description = "Discord user to grant access to"] user: User,
+
+    #[description = "Server to grant access to"]
+    #[autocomplete = "autocomplete_assigned_server"]
+    server: String,
+
+    #[description = "How long the access lasts (e.g. 90m, 12h, 2d12h, 1w)"] duration: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let Some(duration) =
+        duration::parse(&duration).filter(|duration| *duration <= MAX_GRANT_DURATION)
+    else {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft grant")
+            .description(
+                "Invalid duration. Use a number followed by `s`, `m`, `h`, `d` or `w` (e.g. `2d12h`), up to a year.",
+            );
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    };
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let Some(server_id) = sqlx::query!(
+        "SELECT minecraft_servers.id FROM minecraft_servers
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_proxies.discord_guild_id = ?1 AND
+            minecraft_servers.server_type IS NOT NULL AND
+            minecraft_servers.server_name = ?2",
+        guild_id,
+        server
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    .map(|record| record.id) else {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft grant")
+            .description("The specified server doesn't exist or isn't available at this guild.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    };
+
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    let expires_at = now_ms.saturating_add(i64::try_from(duration.as_millis()).unwrap_or(i64::MAX));
+
+    // Granting again replaces the previous expiration
+    let id = snowflake::generate_id();
+    let user_id = u64::from(user.id) as i64;
+    let granted_by = u64::from(ctx.author().id) as i64;
+    sqlx::query!(
+        "INSERT INTO minecraft_access_grants (id, server_id, discord_user_id, granted_by, expires_at)
+        VALUES (?1, ?2, ?3, ?4, ?5)
+        ON CONFLICT(server_id, discord_user_id) DO UPDATE SET granted_by = ?4, expires_at = ?5",
+        id,
+        server_id,
+        user_id,
+        granted_by,
+        expires_at
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[minecraft grant] Granted user {} access to server {} until {}",
+        user.id, server_id, expires_at
+    );
+
+    // Let the proxies re-check the user's linked accounts
+    let records = sqlx::query!(
+        "SELECT id FROM minecraft_users WHERE discord_user_id = ?1",
+        user_id
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    for record in records {
+        minecraft_bridge::authenticated::broadcast_event(
+            Arc::new(ctx.data().clone()),
+            record.id,
+            EventTarget::Guild(ctx.guild_id().unwrap()),
+        )
+        .await;
+    }
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft grant")
+        .description(format!(
+            "<@{}> can join `{}` until <t:{}:f> (<t:{}:R>).",
+            user.id,
+            server,
+            expires_at / 1000,
+            expires_at / 1000
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use poise::CreateReply;
+use poise::serenity_prelude::{Role, Timestamp};
+use tracing::info;
+
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::{
+    Context, Error,
+    utils::{checks, embed},
+};
+
+/// Manage the Minecraft settings of this guild
+#[poise::command(
+    slash_command,
+    guild_only = true,
+    subcommands("adminrole", "restrictions"),
+    subcommand_required = true
+)]
+pub async fn guild(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Choose the role allowed to manage Minecraft proxies and servers in this guild
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_manager")]
+pub async fn adminrole(
+    ctx: Context<'_>,
+
+    #[description = "Twig admin role (empty = Manage Server only)"] role: Option<Role>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let role_id = role.as_ref().map(|role| u64::from(role.id) as i64);
+
+    // Check if role is @everyone
+    if role_id == Some(guild_id) {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft guild admin role")
+            .description("You cannot use the @everyone role.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    sqlx::query!(
+        "INSERT INTO discord_guilds (id, admin_role_id) VALUES (?1, ?2)
+        ON CONFLICT(id) DO UPDATE SET admin_role_id = excluded.admin_role_id",
+        guild_id,
+        role_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[minecraft guild adminrole] Set admin_role_id = {:?} on guild {}",
+        role_id, guild_id
+    );
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft guild admin role")
+        .description(match role {
+            Some(role) => format!(
+                "Members with <@&{}> can now manage the Minecraft proxies and servers of this guild.",
+                role.id
+            ),
+            None => "Only members with Manage Server can now manage the Minecraft proxies and servers of this guild.".to_string(),
+        });
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Deny game access to timed out members or members pending membership screening
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn restrictions(
+    ctx: Context<'_>,
+
+    #[description = "Deny members timed out on Discord"] timed_out: Option<bool>,
+
+    #[description = "Deny members that haven't passed membership screening"] pending: Option<bool>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+
+    if timed_out.is_some() || pending.is_some() {
+        sqlx::query!(
+            "INSERT INTO discord_guilds (id, deny_timed_out, deny_pending)
+            VALUES (?1, COALESCE(?2, FALSE), COALESCE(?3, FALSE))
+            ON CONFLICT(id) DO UPDATE SET
+                deny_timed_out = COALESCE(?2, deny_timed_out),
+                deny_pending = COALESCE(?3, deny_pending)",
+            guild_id,
+            timed_out,
+            pending
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        info!(
+            "[minecraft guild restrictions] Set deny_timed_out = {:?}, deny_pending = {:?} on guild {}",
+            timed_out, pending, guild_id
+        );
+
+        recheck_restricted_players(ctx).await?;
+    }
+
+    let settings = sqlx::query!(
+        "SELECT deny_timed_out, deny_pending FROM discord_guilds WHERE id = ?1",
+        guild_id
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?;
+    let (deny_timed_out, deny_pending) = settings
+        .map(|record| (record.deny_timed_out, record.deny_pending))
+        .unwrap_or_default();
+
+    let status = |denied: bool| if denied { "denied" } else { "allowed" };
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft guild restrictions")
+        .description(format!(
+            "Timed out members are **{}** on the Minecraft servers of this guild.\nMembers pending membership screening are **{}**.",
+            status(deny_timed_out),
+            status(deny_pending)
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Asks the proxies to re-check the linked accounts of the guild's timed out or pending members
+///
+/// Only cached members are considered.
+async fn recheck_restricted_players(ctx: Context<'_>) -> Result<(), Error> {
+    let guild_id = ctx.guild_id().unwrap();
+    let now = Timestamp::now();
+    let user_ids = ctx.data().member_cache.find(guild_id, |member| {
+        member.pending
+            || member
+                .communication_disabled_until
+                .is_some_and(|until| until > now)
+    });
+
+    for user_id in user_ids {
+        let discord_user_id = user_id.get() as i64;
+        let records = sqlx::query!(
+            "SELECT id FROM minecraft_users WHERE discord_user_id = ?1",
+            discord_user_id
+        )
+        .fetch_all(&ctx.data().db)
+        .await?;
+
+        for record in records {
+            minecraft_bridge::authenticated::broadcast_event(
+                Arc::new(ctx.data().clone()),
+                record.id,
+                EventTarget::Guild(guild_id),
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
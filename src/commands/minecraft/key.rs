@@ -0,0 +1,147 @@
+use poise::CreateReply;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    Context, Error,
+    utils::{checks, embed, proxy_keys},
+};
+
+async fn autocomplete_any_proxy(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let mut proxy_ids = Vec::new();
+
+    let pattern = format!("%{}%", partial);
+    if let Ok(rows) = sqlx::query!("SELECT id FROM minecraft_proxies WHERE id LIKE ?1", pattern)
+        .fetch_all(&ctx.data().db)
+        .await
+    {
+        for row in rows {
+            proxy_ids.push(row.id);
+        }
+    }
+
+    info!(
+        "[autocomplete_any_proxy] ({}): {:?}",
+        proxy_ids.len(),
+        proxy_ids
+    );
+
+    proxy_ids
+}
+
+/// Manage the API keys proxies use to reach the gRPC service
+#[poise::command(
+    slash_command,
+    subcommands("issue", "rotate", "revoke"),
+    subcommand_required = true
+)]
+pub async fn key(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Issue an API key for a proxy that doesn't have one yet
+#[poise::command(slash_command, check = "checks::is_owner")]
+pub async fn issue(
+    ctx: Context<'_>,
+
+    #[description = "Proxy ID (UUID) the key is issued for"]
+    #[autocomplete = "autocomplete_any_proxy"]
+    proxy: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // New proxies need a key before they can register, so any proxy ID is accepted
+    if Uuid::parse_str(&proxy).is_err() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy key")
+            .description(format!("`{}` isn't a valid proxy ID (UUID).", proxy));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    if ctx.data().proxy_keys.has_key(&proxy) {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy key")
+            .description("This proxy already has an API key, use `/minecraft key rotate` instead.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let key = proxy_keys::issue_key(&ctx.data().db, &ctx.data().proxy_keys, &proxy).await?;
+    send_key(ctx, &proxy, &key).await
+}
+
+/// Replace the API key of a proxy, invalidating the previous one
+#[poise::command(slash_command, check = "checks::is_owner")]
+pub async fn rotate(
+    ctx: Context<'_>,
+
+    #[description = "Proxy whose key is replaced"]
+    #[autocomplete = "autocomplete_any_proxy"]
+    proxy: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    if !ctx.data().proxy_keys.has_key(&proxy) {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy key")
+            .description("This proxy has no API key, use `/minecraft key issue` instead.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let key = proxy_keys::issue_key(&ctx.data().db, &ctx.data().proxy_keys, &proxy).await?;
+    send_key(ctx, &proxy, &key).await
+}
+
+/// Revoke the API key of a proxy
+#[poise::command(slash_command, check = "checks::is_owner")]
+pub async fn revoke(
+    ctx: Context<'_>,
+
+    #[description = "Proxy whose key is revoked"]
+    #[autocomplete = "autocomplete_any_proxy"]
+    proxy: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let revoked = proxy_keys::revoke_key(&ctx.data().db, &ctx.data().proxy_keys, &proxy).await?;
+
+    let embed = match revoked {
+        true => embed::success()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy key")
+            .description("API key revoked. The proxy can no longer reach the gRPC service."),
+        false => embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy key")
+            .description("This proxy has no API key."),
+    };
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Shows a freshly issued key, which is never displayed again
+async fn send_key(ctx: Context<'_>, proxy: &str, key: &str) -> Result<(), Error> {
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft proxy key")
+        .description(format!(
+            "API key for `{}`:\n```\n{}\n```\nConfigure it in the proxy plugin now, it won't be shown again.",
+            proxy, key
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
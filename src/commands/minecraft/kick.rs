@@ -0,0 +1,142 @@
+use poise::CreateReply;
+use tracing::info;
+
+use crate::grpc::stream::minecraft_bridge;
+use crate::{
+    Context, Error,
+    utils::{checks, embed},
+};
+
+async fn autocomplete_player(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let mut player_names = Vec::new();
+
+    let guild_id_i64 = match ctx.guild_id() {
+        Some(id) => u64::from(id),
+        None => return player_names,
+    } as i64;
+
+    let pattern = format!("%{}%", partial);
+    if let Ok(rows) = sqlx::query!(
+        "SELECT DISTINCT minecraft_users.player_name
+        FROM player_sessions
+        JOIN minecraft_users ON player_sessions.minecraft_user_id = minecraft_users.id
+        JOIN minecraft_servers ON player_sessions.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            player_sessions.ended_at IS NULL AND
+            minecraft_proxies.discord_guild_id = ?1 AND
+            minecraft_users.player_name LIKE ?2
+        LIMIT 25",
+        guild_id_i64,
+        pattern
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    {
+        for row in rows {
+            player_names.push(row.player_name);
+        }
+    }
+
+    info!(
+        "[autocomplete_player] ({}): {:?}",
+        player_names.len(),
+        player_names
+    );
+
+    player_names
+}
+
+/// Kick a player from the Minecraft servers of this guild
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn kick(
+    ctx: Context<'_>,
+
+    #[description = "Minecraft player to kick"]
+    #[autocomplete = "autocomplete_player"]
+    player: String,
+
+    #[description = "Kick message shown to the player"]
+    #[max_length = 256]
+    reason: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    // Only players online on one of the guild's proxies can be kicked
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let Some(session) = sqlx::query!(
+        r#"SELECT
+            minecraft_servers.proxy_id,
+            minecraft_servers.server_name,
+            minecraft_users.player_uuid AS "player_uuid!",
+            minecraft_users.player_name
+        FROM player_sessions
+        JOIN minecraft_users ON player_sessions.minecraft_user_id = minecraft_users.id
+        JOIN minecraft_servers ON player_sessions.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            player_sessions.ended_at IS NULL AND
+            minecraft_proxies.discord_guild_id = ?1 AND
+            minecraft_users.player_name = ?2 COLLATE NOCASE
+        ORDER BY player_sessions.started_at DESC"#,
+        guild_id,
+        player
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    .filter(|session| ctx.data().proxy_sessions.is_connected(&session.proxy_id)) else {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft kick")
+            .description(format!(
+                "`{}` isn't online on this guild's servers.",
+                player
+            ));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    };
+
+    info!(
+        "[kick] {} kicked `{}` from server `{}`",
+        ctx.author().tag(),
+        session.player_name,
+        session.server_name
+    );
+
+    let ack = minecraft_bridge::command::kick_player(
+        ctx.data(),
+        &session.proxy_id,
+        session.player_uuid,
+        session.player_name.clone(),
+        reason.unwrap_or_default(),
+    )
+    .await;
+
+    let embed = match ack {
+        Some(ack) if ack.success => {
+            embed::success().description(ack.message.unwrap_or_else(|| {
+                format!(
+                    "`{}` was kicked from `{}`.",
+                    session.player_name, session.server_name
+                )
+            }))
+        }
+        Some(ack) => embed::warn().description(format!(
+            "The proxy couldn't kick `{}`: {}",
+            session.player_name,
+            ack.message.unwrap_or_else(|| "no reason given".to_string())
+        )),
+        None => embed::warn().description(format!(
+            "The proxy didn't acknowledge the kick of `{}`, it may be offline or not support it.",
+            session.player_name
+        )),
+    }
+    .title("<:minecraft:1435794853517721722>  Minecraft kick");
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
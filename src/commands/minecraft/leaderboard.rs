@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use poise::CreateReply;
+
+use super::autocomplete_assigned_server;
+use crate::{
+    Context, Error,
+    utils::{duration, embed, minecraft::PlaytimePeriod, player_sessions},
+};
+
+/// How many players the leaderboard shows
+const LEADERBOARD_SIZE: i64 = 10;
+
+/// Show the Discord users with the most playtime on the Minecraft servers of this guild
+#[poise::command(slash_command, guild_only = true)]
+pub async fn leaderboard(
+    ctx: Context<'_>,
+
+    #[description = "Time window to rank (defaults to all time)"] period: Option<PlaytimePeriod>,
+
+    #[description = "Only rank playtime on this server"]
+    #[autocomplete = "autocomplete_assigned_server"]
+    server: Option<String>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let period = period.unwrap_or(PlaytimePeriod::AllTime);
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+
+    let players = player_sessions::leaderboard(
+        &ctx.data().db,
+        guild_id,
+        server.as_deref(),
+        period,
+        LEADERBOARD_SIZE,
+    )
+    .await?;
+
+    let scope = match &server {
+        Some(server) => format!("`{}`", server),
+        None => "all servers".to_string(),
+    };
+    let window = match period {
+        PlaytimePeriod::Daily => "today",
+        PlaytimePeriod::Weekly => "this week",
+        PlaytimePeriod::AllTime => "of all time",
+    };
+
+    if players.is_empty() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft leaderboard")
+            .description(format!("Nobody played on {} {}.", scope, window));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let ranking = players
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (discord_user_id, playtime_ms))| {
+            format!(
+                "**{}.** <@{}> • {}",
+                rank + 1,
+                discord_user_id,
+                duration::format(Duration::from_millis(playtime_ms.max(0) as u64))
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft leaderboard")
+        .description(format!(
+            "Top players on {} {}\n\n{}",
+            scope, window, ranking
+        ));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
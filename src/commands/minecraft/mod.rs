@@ -1,18 +1,120 @@
+mod account;
 mod assign;
+mod ban;
+mod broadcast;
+mod chat;
+mod claim;
+mod failpolicy;
+mod grant;
+mod guild;
+mod key;
+mod kick;
+mod leaderboard;
+mod online;
+mod playtime;
+mod proxy;
 mod unassign;
 mod uptime;
+mod verify;
+
+use tracing::info;
 
 use crate::{Context, Data, Error};
 
+use account::*;
 use assign::*;
+use ban::*;
+use broadcast::*;
+use chat::*;
+use claim::*;
+use failpolicy::*;
+use grant::*;
+use guild::*;
+use key::*;
+use kick::*;
+use leaderboard::*;
+use online::*;
+use playtime::*;
+use proxy::*;
 use unassign::*;
 use uptime::*;
+use verify::*;
+
+/// Autocompletes the servers of the guild's proxies
+async fn autocomplete_server(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    server_names(ctx, partial, false).await
+}
+
+/// Autocompletes the servers of the guild's proxies that were assigned a type
+async fn autocomplete_assigned_server(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    server_names(ctx, partial, true).await
+}
+
+/// Returns the names of the guild's servers containing `partial`
+async fn server_names(ctx: Context<'_>, partial: &str, assigned_only: bool) -> Vec<String> {
+    let mut server_names = Vec::new();
+
+    let guild_id_i64 = match ctx.guild_id() {
+        Some(id) => u64::from(id),
+        None => return server_names,
+    } as i64;
+
+    let pattern = format!("%{}%", partial);
+    if let Ok(rows) = sqlx::query!(
+        "SELECT minecraft_servers.server_name
+        FROM minecraft_servers
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_proxies.discord_guild_id = ?1 AND
+            minecraft_servers.active AND
+            (?2 = FALSE OR minecraft_servers.server_type IS NOT NULL) AND
+            minecraft_servers.server_name LIKE ?3",
+        guild_id_i64,
+        assigned_only,
+        pattern
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    {
+        for row in rows {
+            server_names.push(row.server_name);
+        }
+    }
+
+    info!(
+        "[autocomplete_server] ({}): {:?}",
+        server_names.len(),
+        server_names
+    );
+
+    server_names
+}
 
 /// Minecraft command group
 #[poise::command(
     slash_command,
     category = "Minecraft",
-    subcommands("uptime", "assign", "unassign"),
+    subcommands(
+        "uptime",
+        "online",
+        "playtime",
+        "leaderboard",
+        "claim",
+        "assign",
+        "unassign",
+        "proxy",
+        "account",
+        "verify",
+        "key",
+        "guild",
+        "ban",
+        "unban",
+        "kick",
+        "broadcast",
+        "chat",
+        "failpolicy",
+        "grant"
+    ),
     subcommand_required = true
 )]
 pub async fn minecraft(_ctx: Context<'_>) -> Result<(), Error> {
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use poise::CreateReply;
+
+use crate::{Context, Error, utils::embed};
+
+/// Discord limits of an embed (in bytes here, which is never fewer than characters)
+const MAX_FIELDS: usize = 25;
+const MAX_FIELD_LENGTH: usize = 1024;
+/// Below Discord's 6000 to leave room for the title and description
+const MAX_EMBED_LENGTH: usize = 5800;
+
+/// Smallest field worth showing, a single player line
+const MIN_FIELD_LENGTH: usize = 100;
+
+/// Room kept for the "…and N more" line
+const MORE_LINE_LENGTH: usize = 24;
+
+/// Show who is playing on the Minecraft servers of this guild right now
+#[poise::command(slash_command, guild_only = true)]
+pub async fn online(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let records = sqlx::query!(
+        "SELECT
+            minecraft_servers.proxy_id,
+            minecraft_servers.server_name,
+            minecraft_users.player_name,
+            minecraft_users.discord_user_id,
+            player_sessions.started_at
+        FROM player_sessions
+        JOIN minecraft_users ON player_sessions.minecraft_user_id = minecraft_users.id
+        JOIN minecraft_servers ON player_sessions.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE player_sessions.ended_at IS NULL AND minecraft_proxies.discord_guild_id = ?1
+        ORDER BY player_sessions.started_at",
+        guild_id
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    // Only trust the sessions of proxies that are still connected
+    let connected: Vec<String> = ctx
+        .data()
+        .proxy_sessions
+        .connected()
+        .into_iter()
+        .map(|proxy| proxy.proxy_id)
+        .collect();
+
+    let mut servers: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for record in records
+        .into_iter()
+        .filter(|record| connected.contains(&record.proxy_id))
+    {
+        let discord_user = record
+            .discord_user_id
+            .map(|id| format!(" • <@{}>", id))
+            .unwrap_or_default();
+
+        servers.entry(record.server_name).or_default().push(format!(
+            "`{}`{} • since <t:{}:R>",
+            record.player_name,
+            discord_user,
+            record.started_at / 1000
+        ));
+    }
+
+    if servers.is_empty() {
+        let embed = embed::success()
+            .title("<:minecraft:1435794853517721722>  Minecraft online players")
+            .description("Nobody is playing right now.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    // Stay within Discord's embed limits, busy servers are truncated and extra servers summarized
+    let server_count = servers.len();
+    let mut fields = Vec::new();
+    let mut length = 0;
+    for (server_name, players) in servers {
+        let name = format!("{} ({})", server_name, players.len());
+        let budget = MAX_FIELD_LENGTH.min(MAX_EMBED_LENGTH.saturating_sub(length + name.len()));
+        if fields.len() == MAX_FIELDS || budget < MIN_FIELD_LENGTH {
+            break;
+        }
+
+        let value = join_truncated(&players, budget);
+        length += name.len() + value.len();
+        fields.push((name, value, false));
+    }
+
+    let hidden_servers = server_count - fields.len();
+    let mut embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft online players")
+        .fields(fields);
+
+    if hidden_servers > 0 {
+        embed = embed.description(format!("…and {} more servers.", hidden_servers));
+    }
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Joins the lines until `max_length`, replacing the rest with an "…and N more" line
+fn join_truncated(lines: &[String], max_length: usize) -> String {
+    let mut value = String::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        let is_last = index + 1 == lines.len();
+        let reserved = if is_last { 0 } else { MORE_LINE_LENGTH };
+
+        if value.len() + 1 + line.len() + reserved > max_length {
+            value.push_str(&format!("\n…and {} more", lines.len() - index));
+            break;
+        }
+
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(line);
+    }
+
+    value.trim_start().to_string()
+}
@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use poise::CreateReply;
+use poise::serenity_prelude::User;
+
+use crate::{
+    Context, Error,
+    utils::{duration, embed, player_sessions},
+};
+
+/// Show how long a Discord user played on the Minecraft servers of this guild
+#[poise::command(slash_command, guild_only = true)]
+pub async fn playtime(
+    ctx: Context<'_>,
+
+    #[description = "Discord user to show (defaults to you)"] user: Option<User>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let discord_user_id = u64::from(user.id) as i64;
+
+    let servers = player_sessions::playtime(&ctx.data().db, guild_id, discord_user_id).await?;
+
+    if servers.is_empty() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft playtime")
+            .description(format!(
+                "<@{}> hasn't played on this guild's servers yet.",
+                user.id
+            ));
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let format_ms = |ms: i64| duration::format(Duration::from_millis(ms.max(0) as u64));
+
+    let (daily_ms, weekly_ms, total_ms) = servers.iter().fold((0, 0, 0), |totals, server| {
+        (
+            totals.0 + server.daily_ms,
+            totals.1 + server.weekly_ms,
+            totals.2 + server.total_ms,
+        )
+    });
+
+    let fields = servers
+        .into_iter()
+        .map(|server| {
+            (
+                server.server_name,
+                format!(
+                    "Today: **{}**\nThis week: **{}**\nAll time: **{}**",
+                    format_ms(server.daily_ms),
+                    format_ms(server.weekly_ms),
+                    format_ms(server.total_ms)
+                ),
+                true,
+            )
+        })
+        .collect::<Vec<(String, String, bool)>>();
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft playtime")
+        .description(format!(
+            "<@{}> played **{}** today, **{}** this week and **{}** in total.",
+            user.id,
+            format_ms(daily_ms),
+            format_ms(weekly_ms),
+            format_ms(total_ms)
+        ))
+        .fields(fields);
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
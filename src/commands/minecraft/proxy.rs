@@ -0,0 +1,344 @@
+use super::chat::delete_webhook;
+use poise::CreateReply;
+use tracing::info;
+
+use crate::utils::minecraft::RegistrationMode;
+use crate::{
+    Context, Error,
+    utils::{checks, config, embed},
+};
+
+async fn autocomplete_proxy(ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let mut proxy_ids = Vec::new();
+
+    let guild_id_i64 = match ctx.guild_id() {
+        Some(id) => u64::from(id),
+        None => return proxy_ids,
+    } as i64;
+
+    let pattern = format!("%{}%", partial);
+    if let Ok(rows) = sqlx::query!(
+        "SELECT id FROM minecraft_proxies WHERE discord_guild_id = ?1 AND id LIKE ?2",
+        guild_id_i64,
+        pattern
+    )
+    .fetch_all(&ctx.data().db)
+    .await
+    {
+        for row in rows {
+            proxy_ids.push(row.id);
+        }
+    }
+
+    info!(
+        "[autocomplete_proxy] ({}): {:?}",
+        proxy_ids.len(),
+        proxy_ids
+    );
+
+    proxy_ids
+}
+
+/// Manage the Minecraft proxies bound to this guild
+#[poise::command(
+    slash_command,
+    guild_only = true,
+    subcommands("status", "ipcheck", "registration", "unclaim"),
+    subcommand_required = true
+)]
+pub async fn proxy(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// Show which Minecraft proxies of this guild are connected right now
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn status(ctx: Context<'_>) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let proxy_ids: Vec<String> = sqlx::query!(
+        "SELECT id FROM minecraft_proxies WHERE discord_guild_id = ?1",
+        guild_id
+    )
+    .fetch_all(&ctx.data().db)
+    .await?
+    .into_iter()
+    .map(|record| record.id)
+    .collect();
+
+    if proxy_ids.is_empty() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy status")
+            .description("No proxy is claimed by this guild (see `/minecraft claim`).");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let connected = ctx.data().proxy_sessions.connected();
+    let lines: Vec<String> = proxy_ids
+        .iter()
+        .map(|proxy_id| {
+            match connected
+                .iter()
+                .find(|session| &session.proxy_id == proxy_id)
+            {
+                Some(session) => {
+                    let players: Vec<String> = session
+                        .players
+                        .iter()
+                        .map(|player| format!("`{}` ({})", player.player_name, player.server_name))
+                        .collect();
+
+                    format!(
+                        "🟢 `{}` • connected <t:{}:R> • last heartbeat {}s ago\n{} players: {}",
+                        proxy_id,
+                        session.connected_at / 1000,
+                        session.last_heartbeat.as_secs(),
+                        players.len(),
+                        players.join(", ")
+                    )
+                }
+                None => format!("🔴 `{}` • disconnected", proxy_id),
+            }
+        })
+        .collect();
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft proxy status")
+        .description(lines.join("\n"));
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Require players to join from a known IP in addition to their UUID
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn ipcheck(
+    ctx: Context<'_>,
+
+    #[description = "Proxy to configure"]
+    #[autocomplete = "autocomplete_proxy"]
+    proxy: String,
+
+    #[description = "Whether the player's IP must match the linked one"] enabled: bool,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let result = sqlx::query!(
+        "UPDATE minecraft_proxies SET require_ip_match = ?1 WHERE id = ?2 AND discord_guild_id = ?3",
+        enabled,
+        proxy,
+        guild_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy IP check")
+            .description("The specified proxy doesn't exist or isn't available at this guild.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    info!(
+        "[minecraft proxy ipcheck] Set require_ip_match = {} on proxy {}",
+        enabled, proxy
+    );
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft proxy IP check")
+        .description(if enabled {
+            "Players on this proxy must now join from their linked IP address."
+        } else {
+            "Players on this proxy are now identified by their UUID only."
+        });
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Choose how unlinked players on a proxy link their Discord account
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn registration(
+    ctx: Context<'_>,
+
+    #[description = "Proxy to configure"]
+    #[autocomplete = "autocomplete_proxy"]
+    proxy: String,
+
+    #[description = "Registration mode (empty = global default)"] mode: Option<RegistrationMode>,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    if mode == Some(RegistrationMode::OAuth) && !config::get_config().is_oauth_configured() {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy registration")
+            .description(
+                "OAuth isn't configured on this bot (`APP_URL`, `DISCORD_OAUTH_CLIENT_ID` and `DISCORD_OAUTH_CLIENT_SECRET`), use in-game codes instead.",
+            );
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let mode_i64 = mode.map(|mode| mode as i64);
+    let result = sqlx::query!(
+        "UPDATE minecraft_proxies SET registration_mode = ?1 WHERE id = ?2 AND discord_guild_id = ?3",
+        mode_i64,
+        proxy,
+        guild_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy registration")
+            .description("The specified proxy doesn't exist or isn't available at this guild.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    info!(
+        "[minecraft proxy registration] Set registration_mode = {:?} on proxy {}",
+        mode, proxy
+    );
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft proxy registration")
+        .description(match mode {
+            Some(RegistrationMode::OAuth) => {
+                "Unlinked players on this proxy will now link through Discord OAuth."
+            }
+            Some(RegistrationMode::Code) => {
+                "Unlinked players on this proxy will now receive a code to use with `/minecraft verify`."
+            }
+            None => "This proxy now uses the global registration mode.",
+        });
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+/// Release a proxy from this guild and clear its server assignments
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
+pub async fn unclaim(
+    ctx: Context<'_>,
+
+    #[description = "Proxy to release"]
+    #[autocomplete = "autocomplete_proxy"]
+    proxy: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let guild_id = u64::from(ctx.guild_id().unwrap()) as i64;
+    let result = sqlx::query!(
+        "UPDATE minecraft_proxies SET discord_guild_id = NULL WHERE id = ?1 AND discord_guild_id = ?2",
+        proxy,
+        guild_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft proxy unclaim")
+            .description("The specified proxy doesn't exist or isn't available at this guild.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    // Chat channels belong to this guild, remove them with their webhooks
+    let bound_servers = sqlx::query!(
+        "SELECT server_id FROM minecraft_chat_channels
+        WHERE server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1)",
+        proxy
+    )
+    .fetch_all(&ctx.data().db)
+    .await?;
+
+    for record in bound_servers {
+        delete_webhook(ctx, record.server_id).await?;
+    }
+
+    // Server roles belong to this guild, don't leak them to the next owner
+    sqlx::query!(
+        "DELETE FROM minecraft_server_roles
+        WHERE server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1)",
+        proxy
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    // Bans target this guild's members and roles
+    sqlx::query!(
+        "DELETE FROM minecraft_server_denials
+        WHERE server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1)",
+        proxy
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    // Access grants were given to this guild's members
+    sqlx::query!(
+        "DELETE FROM minecraft_access_grants
+        WHERE server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1)",
+        proxy
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    // Last known good accesses would admit this guild's players during outages
+    sqlx::query!(
+        "DELETE FROM minecraft_last_allowed
+        WHERE server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1)",
+        proxy
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE minecraft_servers SET server_type = NULL, role_match_mode = 0, fail_policy = 0
+        WHERE proxy_id = ?1",
+        proxy
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[minecraft proxy unclaim] Proxy `{}` released by guild {}",
+        proxy, guild_id
+    );
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft proxy unclaim")
+        .description("Proxy released. It will print a new claim code the next time it connects.");
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
@@ -1,59 +1,22 @@
-use poise::CreateReply;
+use poise::{CreateReply, serenity_prelude::Role};
 use tracing::info;
 
+use super::autocomplete_assigned_server;
 use crate::{
     Context, Error,
     utils::{checks, embed},
 };
 
-async fn autocomplete_server(ctx: Context<'_>, partial: &str) -> Vec<String> {
-    let mut server_ids = Vec::new();
-
-    let guild_id_i64 = match ctx.guild_id() {
-        Some(id) => u64::from(id),
-        None => return server_ids,
-    } as i64;
-
-    let pattern = format!("%{}%", partial);
-    match sqlx::query!(
-        "SELECT minecraft_servers.server_name 
-        FROM minecraft_servers 
-        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id 
-        WHERE
-            minecraft_proxies.discord_guild_id = ?1 AND
-            minecraft_servers.server_type IS NOT NULL AND
-            minecraft_servers.server_name LIKE ?2",
-        guild_id_i64,
-        pattern
-    )
-    .fetch_all(&ctx.data().db)
-    .await
-    {
-        Ok(rows) => {
-            for row in rows {
-                server_ids.push(row.server_name);
-            }
-        }
-        Err(_) => {}
-    }
-
-    info!(
-        "[autocomplete_server] ({}): {:?}",
-        server_ids.len(),
-        server_ids
-    );
-
-    server_ids
-}
-
-/// Unassign a Discord role from a Minecraft server
-#[poise::command(slash_command, guild_only = true, check = "checks::is_owner")]
+/// Unassign Discord roles from a Minecraft server
+#[poise::command(slash_command, guild_only = true, check = "checks::is_guild_admin")]
 pub async fn unassign(
     ctx: Context<'_>,
 
     #[description = "Server to unassign the role from"]
-    #[autocomplete = "autocomplete_server"]
+    #[autocomplete = "autocomplete_assigned_server"]
     server: String,
+
+    #[description = "Role to remove (empty = unassign the server)"] role: Option<Role>,
 ) -> Result<(), Error> {
     ctx.defer_ephemeral().await?;
 
@@ -85,9 +48,61 @@ pub async fn unassign(
 
     let server_id = server_result.unwrap().id;
 
-    // Update server discord_role_id
+    // Remove a single role, the server stays assigned while it has roles left
+    if let Some(role) = role {
+        let role_id_i64 = u64::from(role.id) as i64;
+        let result = sqlx::query!(
+            "DELETE FROM minecraft_server_roles WHERE server_id = ?1 AND discord_role_id = ?2",
+            server_id,
+            role_id_i64
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            let embed = embed::warn()
+                .title("<:minecraft:1435794853517721722>  /minecraft unassign server")
+                .description("The specified role isn't assigned to this server.");
+
+            ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+                .await?;
+
+            return Ok(());
+        }
+
+        info!(
+            "[minecraft unassign] Removed role {} from server {}",
+            role.id, server_id
+        );
+
+        sqlx::query!(
+            "UPDATE minecraft_servers SET server_type = NULL
+         WHERE id = ?1 AND NOT EXISTS (SELECT 1 FROM minecraft_server_roles WHERE server_id = ?1)",
+            server_id
+        )
+        .execute(&ctx.data().db)
+        .await?;
+
+        let embed = embed::success()
+            .title("<:minecraft:1435794853517721722>  Minecraft unassign server")
+            .description("Role successfully removed from the specified server.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    // Remove every role of the server
+    sqlx::query!(
+        "DELETE FROM minecraft_server_roles WHERE server_id = ?1",
+        server_id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
     sqlx::query!(
-        "UPDATE minecraft_servers SET server_type = NULL, discord_role_id = NULL
+        "UPDATE minecraft_servers SET server_type = NULL
          WHERE id = ?1",
         server_id
     )
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use poise::CreateReply;
+use tracing::{info, warn};
+
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::utils::linking::{self, LinkError};
+use crate::{
+    Context, Error,
+    utils::{config, embed, snowflake},
+};
+
+/// Link your Minecraft account using the code shown in-game
+#[poise::command(slash_command)]
+pub async fn verify(
+    ctx: Context<'_>,
+
+    #[description = "Code shown when joining the Minecraft server"] code: String,
+) -> Result<(), Error> {
+    ctx.defer_ephemeral().await?;
+
+    let code = code.trim().to_uppercase();
+    let registration = sqlx::query!(
+        "SELECT minecraft_registrations.id, minecraft_registrations.minecraft_user_id,
+            COALESCE(minecraft_registrations.player_ip, minecraft_users.player_ip) as player_ip
+        FROM minecraft_registrations
+        JOIN minecraft_users ON minecraft_registrations.minecraft_user_id = minecraft_users.id
+        WHERE minecraft_registrations.verification_code = ?1",
+        code
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?;
+
+    // Check if registration is still within its TTL
+    let registration_ttl_ms = config::get_config().registration_ttl_secs * 1000;
+    let Some(registration) = registration.filter(|registration| {
+        snowflake::is_snowflake_recent(registration.id, registration_ttl_ms)
+    }) else {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft verify")
+            .description("The specified code is invalid or has expired.");
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    };
+
+    let user_id = ctx.author().id.get() as i64;
+    let player_ip = registration.player_ip.unwrap_or_default();
+
+    let description = match linking::link_minecraft_user(
+        &ctx.data().db,
+        registration.minecraft_user_id,
+        user_id,
+        &player_ip,
+    )
+    .await
+    {
+        Ok(()) => None,
+        Err(LinkError::AccountLinkedElsewhere) => {
+            Some("This Minecraft account is already linked to another Discord user.")
+        }
+        Err(LinkError::NameLinkedElsewhere) => {
+            Some("This Minecraft name is already linked to another Discord user.")
+        }
+        Err(LinkError::Database(e)) => {
+            warn!(
+                "[minecraft verify] Failed to link Minecraft user {}: {}",
+                registration.minecraft_user_id, e
+            );
+
+            return Err(Box::new(e));
+        }
+    };
+
+    if let Some(description) = description {
+        let embed = embed::warn()
+            .title("<:minecraft:1435794853517721722>  Minecraft verify")
+            .description(description);
+
+        ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+            .await?;
+
+        return Ok(());
+    }
+
+    // Registrations are single-use
+    sqlx::query!(
+        "DELETE FROM minecraft_registrations WHERE id = ?1",
+        registration.id
+    )
+    .execute(&ctx.data().db)
+    .await?;
+
+    info!(
+        "[minecraft verify] Linked Minecraft user {} to {}",
+        registration.minecraft_user_id, user_id
+    );
+
+    minecraft_bridge::authenticated::broadcast_event(
+        Arc::new(ctx.data().clone()),
+        registration.minecraft_user_id,
+        EventTarget::All,
+    )
+    .await;
+
+    let embed = embed::success()
+        .title("<:minecraft:1435794853517721722>  Minecraft verify")
+        .description("Minecraft account successfully linked. You can now join the server.");
+
+    ctx.send(CreateReply::default().embed(embed).ephemeral(true))
+        .await?;
+
+    Ok(())
+}
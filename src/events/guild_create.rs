@@ -0,0 +1,41 @@
+use poise::serenity_prelude::{self as serenity};
+use tracing::info;
+
+use super::guild_member_update;
+use crate::{Data, Error};
+
+/// Handles the GuildCreate event by seeding the member cache and re-arming timeout re-checks
+pub async fn handle(
+    ctx: &serenity::Context,
+    data: &Data,
+    guild: &serenity::Guild,
+) -> Result<(), Error> {
+    info!(
+        "[guild_create::handle] Caching {} member(s) of guild {}",
+        guild.members.len(),
+        guild.id
+    );
+
+    data.member_cache
+        .insert_many(guild.members.values().cloned());
+
+    guild_member_update::rearm_timeout_rechecks(data, guild.id, guild.members.values()).await?;
+
+    // Large guilds only send a subset of their members, request the rest
+    if guild.large || (guild.members.len() as u64) < guild.member_count {
+        info!(
+            "[guild_create::handle] Requesting member chunks for guild {} ({} members)",
+            guild.id, guild.member_count
+        );
+
+        ctx.shard.chunk_guild(
+            guild.id,
+            None,
+            false,
+            serenity::ChunkGuildFilter::None,
+            None,
+        );
+    }
+
+    Ok(())
+}
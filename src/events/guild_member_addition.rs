@@ -0,0 +1,21 @@
+use poise::serenity_prelude::{self as serenity};
+use tracing::info;
+
+use crate::{Data, Error};
+
+/// Handles the GuildMemberAddition event by caching the new member
+pub async fn handle(
+    _ctx: &serenity::Context,
+    data: &Data,
+    new_member: &serenity::Member,
+) -> Result<(), Error> {
+    info!(
+        "[guild_member_addition::handle] User {} joined guild {}",
+        new_member.user.tag(),
+        new_member.guild_id
+    );
+
+    data.member_cache.insert(new_member.clone());
+
+    Ok(())
+}
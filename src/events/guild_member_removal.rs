@@ -18,10 +18,13 @@ pub async fn handle(
         guild_id
     );
 
+    data.member_cache.remove(*guild_id, user.id);
+    data.timeout_timers.cancel(*guild_id, user.id);
+
     let user_id = user.id.get() as i64;
 
     match sqlx::query!(
-        "SELECT player_name, player_ipv4 FROM minecraft_users WHERE discord_user_id = $1",
+        "SELECT player_uuid, player_name, player_ip FROM minecraft_users WHERE discord_user_id = $1",
         user_id
     )
     .fetch_all(&data.db)
@@ -30,18 +33,20 @@ pub async fn handle(
         Ok(records) => {
             for record in records {
                 let player_name = record.player_name;
-                let player_ipv4 = record.player_ipv4;
+                let player_ip = record.player_ip;
 
                 info!(
                     "[guild_member_removal::handle] Found linked Minecraft user: {} (IP: {})",
-                    player_name, player_ipv4
+                    player_name, player_ip
                 );
 
                 // Send PlayerDisconnect event to Minecraft server via gRPC
                 minecraft_bridge::disconnect::guild_member_removal(
                     Arc::new(data.clone()),
+                    *guild_id,
+                    record.player_uuid,
                     player_name,
-                    player_ipv4,
+                    player_ip,
                 )
                 .await;
             }
@@ -1,50 +1,263 @@
+use std::collections::HashSet;
 use std::sync::Arc;
+use std::time::Duration;
 
 use poise::serenity_prelude as serenity;
-use tracing::info;
+use tracing::{debug, info};
 
-use crate::{Data, Error, grpc::stream::minecraft_bridge};
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::{Data, Error};
 
 pub async fn handle(
     _ctx: &serenity::Context,
     data: &Data,
-    _old_if_available: &Option<serenity::Member>,
-    _new: &Option<serenity::Member>,
+    old_if_available: &Option<serenity::Member>,
+    new: &Option<serenity::Member>,
     event: &serenity::GuildMemberUpdateEvent,
 ) -> Result<(), Error> {
-    // Check if user is registered in DB
     info!(
         "[guild_member_update::handle] Guild member updated: {} in guild {}",
         event.user.tag(),
         event.guild_id
     );
 
-    let user_id = event.user.id.get() as i64;
+    // Keep the previous state around before the cache is updated
+    let old = old_if_available
+        .clone()
+        .or_else(|| data.member_cache.get(event.guild_id, event.user.id));
+
+    data.member_cache.update(event, new.as_ref());
+
+    // Only the proxies bound to this guild care about its members
+    let guild_id = event.guild_id.get() as i64;
+    let proxy_ids = guild_proxy_ids(data, event.guild_id).await?;
+
+    if proxy_ids.is_empty() {
+        debug!(
+            "[guild_member_update::handle] Guild {} has no Minecraft proxies",
+            event.guild_id
+        );
+
+        return Ok(());
+    }
+
+    let (deny_timed_out, deny_pending) = sqlx::query!(
+        "SELECT deny_timed_out, deny_pending FROM discord_guilds WHERE id = ?1",
+        guild_id
+    )
+    .fetch_optional(&data.db)
+    .await?
+    .map(|record| (record.deny_timed_out, record.deny_pending))
+    .unwrap_or_default();
+
+    // Roles allowed or denied on the guild's servers
+    let access_role_ids: HashSet<serenity::RoleId> = sqlx::query!(
+        r#"SELECT minecraft_server_roles.discord_role_id AS "discord_role_id!"
+        FROM minecraft_server_roles
+        JOIN minecraft_servers ON minecraft_server_roles.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE minecraft_proxies.discord_guild_id = ?1
+        UNION
+        SELECT minecraft_server_denials.discord_role_id AS "discord_role_id!"
+        FROM minecraft_server_denials
+        JOIN minecraft_servers ON minecraft_server_denials.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_proxies.discord_guild_id = ?1 AND
+            minecraft_server_denials.discord_role_id IS NOT NULL"#,
+        guild_id
+    )
+    .fetch_all(&data.db)
+    .await?
+    .into_iter()
+    .map(|record| serenity::RoleId::new(record.discord_role_id as u64))
+    .collect();
+
+    let now = serenity::Timestamp::now();
+    let is_timed_out = |until: Option<serenity::Timestamp>| until.is_some_and(|until| until > now);
+
+    // Discord doesn't send an update when a timeout runs out, re-check the player then
+    if deny_timed_out {
+        schedule_timeout_recheck(
+            data,
+            event.guild_id,
+            event.user.id,
+            event.communication_disabled_until,
+            &proxy_ids,
+        );
+    }
+
+    // Without the previous state, any change could be relevant
+    let access_changed = match old {
+        Some(old) => {
+            access_role_ids
+                .iter()
+                .any(|role_id| old.roles.contains(role_id) != event.roles.contains(role_id))
+                || (deny_timed_out
+                    && is_timed_out(old.communication_disabled_until)
+                        != is_timed_out(event.communication_disabled_until))
+                || (deny_pending && old.pending != event.pending)
+        }
+        None => true,
+    };
+
+    if !access_changed {
+        debug!(
+            "[guild_member_update::handle] No access-relevant change for {} in guild {}",
+            event.user.tag(),
+            event.guild_id
+        );
+
+        return Ok(());
+    }
+
+    recheck_linked_accounts(data, event.user.id, &proxy_ids).await;
+
+    Ok(())
+}
+
+/// Schedules the timeout re-checks of members received in bulk, e.g. on `GuildCreate`
+///
+/// Re-checks only live in memory, so they're re-armed whenever Discord sends
+/// the guild's members again, such as after a restart.
+pub async fn rearm_timeout_rechecks<'a>(
+    data: &Data,
+    guild_id: serenity::GuildId,
+    members: impl IntoIterator<Item = &'a serenity::Member>,
+) -> Result<(), Error> {
+    let guild_id_i64 = guild_id.get() as i64;
+    let deny_timed_out = sqlx::query!(
+        "SELECT deny_timed_out FROM discord_guilds WHERE id = ?1",
+        guild_id_i64
+    )
+    .fetch_optional(&data.db)
+    .await?
+    .is_some_and(|record| record.deny_timed_out);
+
+    if !deny_timed_out {
+        return Ok(());
+    }
+
+    let proxy_ids = guild_proxy_ids(data, guild_id).await?;
+    if proxy_ids.is_empty() {
+        return Ok(());
+    }
+
+    let now = serenity::Timestamp::now();
+    let mut scheduled = 0;
+    for member in members {
+        if member
+            .communication_disabled_until
+            .is_some_and(|until| until > now)
+        {
+            schedule_timeout_recheck(
+                data,
+                guild_id,
+                member.user.id,
+                member.communication_disabled_until,
+                &proxy_ids,
+            );
+            scheduled += 1;
+        }
+    }
+
+    if scheduled > 0 {
+        info!(
+            "[guild_member_update::rearm_timeout_rechecks] Scheduled {} timeout re-check(s) in guild {}",
+            scheduled, guild_id
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the proxies bound to the guild
+async fn guild_proxy_ids(
+    data: &Data,
+    guild_id: serenity::GuildId,
+) -> Result<Vec<String>, sqlx::Error> {
+    let guild_id = guild_id.get() as i64;
+
+    Ok(sqlx::query!(
+        "SELECT id FROM minecraft_proxies WHERE discord_guild_id = ?1",
+        guild_id
+    )
+    .fetch_all(&data.db)
+    .await?
+    .into_iter()
+    .map(|record| record.id)
+    .collect())
+}
+
+/// Keeps one re-check per member for when their timeout runs out
+///
+/// Updates that don't change the timeout (nickname, avatar...) leave the
+/// pending re-check alone, and lifting a timeout early cancels it.
+fn schedule_timeout_recheck(
+    data: &Data,
+    guild_id: serenity::GuildId,
+    user_id: serenity::UserId,
+    until: Option<serenity::Timestamp>,
+    proxy_ids: &[String],
+) {
+    let timers = &data.timeout_timers;
+
+    let until = until.filter(|until| *until > serenity::Timestamp::now());
+
+    let Some(until) = until else {
+        timers.cancel(guild_id, user_id);
+        return;
+    };
+
+    if timers.ends_at(guild_id, user_id) == Some(until) {
+        return;
+    }
+
+    let remaining_secs = until.unix_timestamp() - serenity::Timestamp::now().unix_timestamp();
+    let task_data = data.clone();
+    let proxy_ids = proxy_ids.to_vec();
+
+    let task = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(remaining_secs.max(0) as u64 + 1)).await;
+
+        info!(
+            "[guild_member_update::schedule_timeout_recheck] Timeout of Discord user ID {} ended",
+            user_id
+        );
+
+        task_data.timeout_timers.finish(guild_id, user_id, until);
+        recheck_linked_accounts(&task_data, user_id, &proxy_ids).await;
+    });
+
+    timers.set(guild_id, user_id, until, task);
+}
+
+/// Asks the given proxies to re-check every Minecraft account linked to the Discord user
+async fn recheck_linked_accounts(data: &Data, user_id: serenity::UserId, proxy_ids: &[String]) {
+    let user_id = user_id.get() as i64;
 
     if let Ok(records) = sqlx::query!(
-        "SELECT player_name, player_ipv4 FROM minecraft_users WHERE discord_user_id = $1",
+        "SELECT player_uuid, player_name, player_ip FROM minecraft_users WHERE discord_user_id = $1",
         user_id
     )
     .fetch_all(&data.db)
     .await
     {
         info!(
-            "[guild_member_update::handle] Found {} linked Minecraft account(s) for Discord user ID {}",
+            "[guild_member_update::recheck_linked_accounts] Found {} linked Minecraft account(s) for Discord user ID {}",
             records.len(),
             user_id
         );
 
         for record in records {
-            let (player_name, player_ipv4) = (record.player_name, record.player_ipv4);
-
-            minecraft_bridge::disconnect::guild_member_removal(
+            minecraft_bridge::disconnect::player_update(
                 Arc::new(data.clone()),
-                player_name,
-                player_ipv4,
+                EventTarget::Proxies(proxy_ids.to_vec()),
+                record.player_uuid,
+                record.player_name,
+                record.player_ip,
             )
             .await;
         }
     };
-
-    Ok(())
 }
@@ -0,0 +1,28 @@
+use poise::serenity_prelude::{self as serenity};
+use tracing::debug;
+
+use super::guild_member_update;
+use crate::{Data, Error};
+
+/// Handles the GuildMembersChunk event by filling the member cache and re-arming timeout re-checks
+pub async fn handle(
+    _ctx: &serenity::Context,
+    data: &Data,
+    chunk: &serenity::GuildMembersChunkEvent,
+) -> Result<(), Error> {
+    debug!(
+        "[guild_members_chunk::handle] Caching chunk {}/{} ({} members) of guild {}",
+        chunk.chunk_index + 1,
+        chunk.chunk_count,
+        chunk.members.len(),
+        chunk.guild_id
+    );
+
+    data.member_cache
+        .insert_many(chunk.members.values().cloned());
+
+    guild_member_update::rearm_timeout_rechecks(data, chunk.guild_id, chunk.members.values())
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use poise::serenity_prelude::{self as serenity};
+use tracing::{info, warn};
+
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::utils::minecraft::TrustedIpStatus;
+use crate::utils::{embed, snowflake, trusted_ips};
+use crate::{Data, Error};
+
+/// Handles component interactions that are not bound to a command
+pub async fn handle(
+    ctx: &serenity::Context,
+    data: &Data,
+    interaction: &serenity::Interaction,
+) -> Result<(), Error> {
+    let serenity::Interaction::Component(component) = interaction else {
+        return Ok(());
+    };
+
+    let mut parts = component.data.custom_id.split(':');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some("trusted_ip"), Some(action), Some(id)) => {
+            let Ok(trusted_ip_id) = id.parse::<i64>() else {
+                return Ok(());
+            };
+
+            handle_trusted_ip(ctx, data, component, action == "approve", trusted_ip_id).await
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Approves or denies a pending trusted IP from the DM buttons
+async fn handle_trusted_ip(
+    ctx: &serenity::Context,
+    data: &Data,
+    component: &serenity::ComponentInteraction,
+    approve: bool,
+    trusted_ip_id: i64,
+) -> Result<(), Error> {
+    let user_id = component.user.id.get() as i64;
+    let pending = TrustedIpStatus::Pending as i64;
+
+    // Only the linked Discord user can answer their own pending requests
+    let record = sqlx::query!(
+        "SELECT minecraft_trusted_ips.minecraft_user_id, minecraft_trusted_ips.player_ip
+        FROM minecraft_trusted_ips
+        JOIN minecraft_users ON minecraft_trusted_ips.minecraft_user_id = minecraft_users.id
+        WHERE minecraft_trusted_ips.id = ?1
+            AND minecraft_trusted_ips.status = ?2
+            AND minecraft_users.discord_user_id = ?3",
+        trusted_ip_id,
+        pending,
+        user_id
+    )
+    .fetch_optional(&data.db)
+    .await?;
+
+    let embed = match record {
+        Some(record)
+            if snowflake::is_snowflake_recent(trusted_ip_id, trusted_ips::APPROVAL_EXPIRY_MS) =>
+        {
+            let status = match approve {
+                true => TrustedIpStatus::Approved as i64,
+                false => TrustedIpStatus::Denied as i64,
+            };
+
+            sqlx::query!(
+                "UPDATE minecraft_trusted_ips SET status = ?1 WHERE id = ?2",
+                status,
+                trusted_ip_id
+            )
+            .execute(&data.db)
+            .await?;
+
+            info!(
+                "[interaction_create::handle] User {} {} IP {} for Minecraft user {}",
+                user_id,
+                if approve { "approved" } else { "denied" },
+                record.player_ip,
+                record.minecraft_user_id
+            );
+
+            // Let the proxies re-check the player
+            minecraft_bridge::authenticated::broadcast_event(
+                Arc::new(data.clone()),
+                record.minecraft_user_id,
+                EventTarget::All,
+            )
+            .await;
+
+            if approve {
+                embed::success()
+                    .title("<:minecraft:1435794853517721722>  New Minecraft login")
+                    .description(format!(
+                        "`{}` is now a trusted IP address.",
+                        record.player_ip
+                    ))
+            } else {
+                embed::warn()
+                    .title("<:minecraft:1435794853517721722>  New Minecraft login")
+                    .description(format!("`{}` was denied.", record.player_ip))
+            }
+        }
+        _ => {
+            warn!(
+                "[interaction_create::handle] Trusted IP request {} is invalid or expired",
+                trusted_ip_id
+            );
+
+            embed::warn()
+                .title("<:minecraft:1435794853517721722>  New Minecraft login")
+                .description("This request has expired or was already answered.")
+        }
+    };
+
+    component
+        .create_response(
+            ctx,
+            serenity::CreateInteractionResponse::UpdateMessage(
+                serenity::CreateInteractionResponseMessage::new()
+                    .embed(embed)
+                    .components(vec![]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
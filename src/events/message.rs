@@ -0,0 +1,74 @@
+use poise::serenity_prelude as serenity;
+use tracing::debug;
+
+use crate::grpc::stream::minecraft_bridge;
+use crate::utils::chat;
+use crate::{Data, Error};
+
+/// Relays messages written in a bound chat channel to the Minecraft server
+pub async fn handle(
+    ctx: &serenity::Context,
+    data: &Data,
+    new_message: &serenity::Message,
+) -> Result<(), Error> {
+    // Ignore bots, and the webhook posting the players' messages
+    if new_message.author.bot || new_message.webhook_id.is_some() {
+        return Ok(());
+    }
+
+    let Some(guild_id) = new_message.guild_id else {
+        return Ok(());
+    };
+
+    // The proxy must still belong to the channel's guild
+    let channel_id = new_message.channel_id.get() as i64;
+    let guild_id = guild_id.get() as i64;
+    let Some(binding) = sqlx::query!(
+        "SELECT minecraft_servers.proxy_id, minecraft_servers.server_name
+        FROM minecraft_chat_channels
+        JOIN minecraft_servers ON minecraft_chat_channels.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_chat_channels.discord_channel_id = ?1 AND
+            minecraft_proxies.discord_guild_id = ?2",
+        channel_id,
+        guild_id
+    )
+    .fetch_optional(&data.db)
+    .await?
+    else {
+        return Ok(());
+    };
+
+    // Mentions become readable names before the markdown is stripped
+    let mut message = chat::sanitize_for_minecraft(&new_message.content_safe(ctx));
+    if !new_message.attachments.is_empty() {
+        message = format!("{} [attachment]", message).trim().to_string();
+    }
+
+    if message.is_empty() {
+        return Ok(());
+    }
+
+    let author_name = new_message
+        .author_nick(ctx)
+        .await
+        .unwrap_or_else(|| new_message.author.display_name().to_string());
+
+    debug!(
+        "[message::handle] Relaying a message of {} to server `{}`",
+        new_message.author.tag(),
+        binding.server_name
+    );
+
+    minecraft_bridge::chat::relay_to_minecraft(
+        data,
+        binding.proxy_id,
+        binding.server_name,
+        chat::sanitize_for_minecraft(&author_name),
+        message,
+    )
+    .await;
+
+    Ok(())
+}
@@ -1,5 +1,10 @@
+mod guild_create;
+mod guild_member_addition;
 mod guild_member_removal;
 mod guild_member_update;
+mod guild_members_chunk;
+mod interaction_create;
+mod message;
 mod ready;
 
 use crate::{Data, Error};
@@ -22,6 +27,15 @@ pub async fn event_handler(
         serenity::FullEvent::Ready { data_about_bot } => {
             ready::handle(ctx, data, data_about_bot).await?;
         }
+        serenity::FullEvent::GuildCreate { guild, .. } => {
+            guild_create::handle(ctx, data, guild).await?;
+        }
+        serenity::FullEvent::GuildMembersChunk { chunk } => {
+            guild_members_chunk::handle(ctx, data, chunk).await?;
+        }
+        serenity::FullEvent::GuildMemberAddition { new_member } => {
+            guild_member_addition::handle(ctx, data, new_member).await?;
+        }
         serenity::FullEvent::GuildMemberRemoval {
             guild_id,
             user,
@@ -37,6 +51,14 @@ pub async fn event_handler(
         } => {
             guild_member_update::handle(ctx, data, old_if_available, new, event).await?;
         }
+        serenity::FullEvent::InteractionCreate { interaction } => {
+            interaction_create::handle(ctx, data, interaction).await?;
+        }
+        serenity::FullEvent::Message { new_message }
+            if crate::utils::config::get_config().chat_relay =>
+        {
+            message::handle(ctx, data, new_message).await?;
+        }
         _ => {}
     }
 
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tracing::warn;
+
+use crate::utils::config;
+use crate::utils::proxy_keys::{self, ProxyKeyStore};
+
+/// Proxy authenticated by [`ProxyAuthInterceptor`], stored in the request extensions
+#[derive(Clone)]
+pub struct AuthenticatedProxy {
+    pub proxy_id: String,
+    /// Hash of the API key the request was made with
+    pub key_hash: String,
+}
+
+/// Rejects requests without a valid `authorization: Bearer <key>` proxy API key
+#[derive(Clone)]
+pub struct ProxyAuthInterceptor {
+    keys: Arc<ProxyKeyStore>,
+}
+
+impl ProxyAuthInterceptor {
+    pub fn new(keys: Arc<ProxyKeyStore>) -> Self {
+        Self { keys }
+    }
+}
+
+impl Interceptor for ProxyAuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if !config::get_config().grpc_auth {
+            return Ok(request);
+        }
+
+        let Some(header) = request.metadata().get("authorization") else {
+            warn!("[gRPC::auth] Request without an API key");
+            return Err(Status::unauthenticated("Missing proxy API key"));
+        };
+
+        let Some(key) = header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+        else {
+            warn!("[gRPC::auth] Request with a malformed authorization header");
+            return Err(Status::unauthenticated(
+                "Authorization header must be `Bearer <key>`",
+            ));
+        };
+
+        let key_hash = proxy_keys::hash_key(key.trim());
+        let Some(proxy_id) = self.keys.authenticate(&key_hash) else {
+            warn!("[gRPC::auth] Request with an invalid or revoked API key");
+            return Err(Status::unauthenticated("Invalid or revoked proxy API key"));
+        };
+
+        request
+            .extensions_mut()
+            .insert(AuthenticatedProxy { proxy_id, key_hash });
+        Ok(request)
+    }
+}
+
+/// Ensures the request was authenticated with the API key of `proxy_id`
+///
+/// # Errors
+/// Returns `PermissionDenied` if the API key belongs to another proxy
+pub fn authorize_proxy<T>(request: &Request<T>, proxy_id: &str) -> Result<(), Status> {
+    if !config::get_config().grpc_auth {
+        return Ok(());
+    }
+
+    match request.extensions().get::<AuthenticatedProxy>() {
+        Some(AuthenticatedProxy {
+            proxy_id: authenticated,
+            ..
+        }) if authenticated == proxy_id => Ok(()),
+        Some(AuthenticatedProxy {
+            proxy_id: authenticated,
+            ..
+        }) => {
+            warn!(
+                "[gRPC::auth] Proxy `{}` tried to act as proxy `{}`",
+                authenticated, proxy_id
+            );
+
+            Err(Status::permission_denied(
+                "API key does not belong to this proxy",
+            ))
+        }
+        None => Err(Status::unauthenticated("Missing proxy API key")),
+    }
+}
+
+/// Returns the hash of the API key the request was authenticated with
+pub fn key_hash<T>(request: &Request<T>) -> Option<String> {
+    request
+        .extensions()
+        .get::<AuthenticatedProxy>()
+        .map(|proxy| proxy.key_hash.clone())
+}
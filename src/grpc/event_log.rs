@@ -0,0 +1,106 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use prost::Message;
+use sqlx::SqlitePool;
+use tokio::sync::{Mutex, MutexGuard};
+use tracing::warn;
+
+use super::minecraft_bridge::ServerEvent;
+
+/// Serializes appends so events reach the broadcast channel in sequence order
+static APPEND_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Persists an event and sets its sequence number
+///
+/// The returned guard must be held until the event is broadcast, otherwise
+/// subscribers could receive a later sequence first and skip this one.
+pub async fn append(
+    db: &SqlitePool,
+    event: &mut ServerEvent,
+) -> Result<MutexGuard<'static, ()>, sqlx::Error> {
+    let guard = APPEND_LOCK.lock().await;
+
+    let encoded = event.encode_to_vec();
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    let sequence = sqlx::query!(
+        "INSERT INTO minecraft_events (event, created_at) VALUES (?1, ?2)",
+        encoded,
+        created_at
+    )
+    .execute(db)
+    .await?
+    .last_insert_rowid();
+
+    event.sequence = sequence as u64;
+
+    Ok(guard)
+}
+
+/// Returns the logged events after `sequence`, oldest first
+pub async fn since(db: &SqlitePool, sequence: u64) -> Result<Vec<ServerEvent>, sqlx::Error> {
+    let sequence = sequence as i64;
+    let records = sqlx::query!(
+        "SELECT sequence, event FROM minecraft_events WHERE sequence > ?1 ORDER BY sequence",
+        sequence
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .filter_map(
+            |record| match ServerEvent::decode(record.event.as_slice()) {
+                Ok(mut event) => {
+                    event.sequence = record.sequence as u64;
+                    Some(event)
+                }
+                Err(e) => {
+                    warn!(
+                        "[event_log::since] Failed to decode event {}: {}",
+                        record.sequence, e
+                    );
+                    None
+                }
+            },
+        )
+        .collect())
+}
+
+/// Returns the sequence of the latest logged event (0 if the log is empty)
+///
+/// Waits for in-flight appends, so every event up to the returned sequence
+/// has already been sent to the broadcast channel.
+pub async fn latest_sequence(db: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let _guard = APPEND_LOCK.lock().await;
+
+    Ok(bounds(db).await?.map(|(_, latest)| latest).unwrap_or(0))
+}
+
+/// Returns the sequence of the oldest and latest logged events, if any
+pub async fn bounds(db: &SqlitePool) -> Result<Option<(u64, u64)>, sqlx::Error> {
+    let record = sqlx::query!(
+        r#"SELECT MIN(sequence) AS "oldest?: i64", MAX(sequence) AS "latest?: i64" FROM minecraft_events"#
+    )
+    .fetch_one(db)
+    .await?;
+
+    Ok(record
+        .oldest
+        .zip(record.latest)
+        .map(|(oldest, latest)| (oldest as u64, latest as u64)))
+}
+
+/// Deletes the events created before `cutoff_ms`, returning how many were deleted
+pub async fn purge_before(db: &SqlitePool, cutoff_ms: i64) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query!(
+        "DELETE FROM minecraft_events WHERE created_at < ?1",
+        cutoff_ms
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
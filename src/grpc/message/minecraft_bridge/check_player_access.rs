@@ -1,14 +1,25 @@
 use poise::serenity_prelude::{self as serenity};
 use reqwest::Url;
-use std::net::Ipv4Addr;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tonic::{Request, Response, Status};
 use tracing::{info, warn};
 use uuid::Uuid;
 
+use super::denial::{denied, member_error_reason};
+use super::fail_policy::{self, FailPolicyTarget};
+use super::player::{get_or_create_player, parse_player_uuid, update_player_ip};
 use crate::grpc::GrpcServiceState;
-use crate::grpc::minecraft_bridge::{AccessStatus, PlayerAccessRequest, PlayerAccessResponse};
-use crate::utils::config;
-use crate::utils::minecraft::MinecraftServerType;
+use crate::grpc::auth::authorize_proxy;
+use crate::grpc::minecraft_bridge::{
+    AccessStatus, DenialReason, PlayerAccessRequest, PlayerAccessResponse,
+};
+use crate::utils::embed;
+use crate::utils::linking;
+use crate::utils::minecraft::{
+    FailPolicy, MinecraftServerType, RegistrationMode, RoleMatchMode, TrustedIpStatus,
+};
+use crate::utils::trusted_ips::{self, IpTrust};
+use crate::utils::{config, ip};
 
 const DISCORD_OAUTH_BASE_URL: &str = "https://discord.com/oauth2/authorize";
 
@@ -17,15 +28,24 @@ pub async fn check_player_access(
     state: &GrpcServiceState,
     request: Request<PlayerAccessRequest>,
 ) -> Result<Response<PlayerAccessResponse>, Status> {
+    authorize_proxy(&request, &request.get_ref().proxy_id)?;
+
     let access_request = request.into_inner();
     let player_name = access_request.player_name;
-    let player_ipv4 = access_request.player_ipv4;
     let server_name = access_request.server_name;
     let proxy_id = access_request.proxy_id;
+    let locale = access_request.locale;
+
+    // Older proxies only send the legacy IPv4 field
+    let raw_player_ip = if access_request.player_ip.is_empty() {
+        access_request.player_ipv4
+    } else {
+        access_request.player_ip
+    };
 
     info!(
         "[gRPC::CheckPlayerAccess] Checking access for player `{}` ({}) to server `{}` (`{}`)",
-        player_name, player_ipv4, server_name, proxy_id
+        player_name, raw_player_ip, server_name, proxy_id
     );
 
     // Validate player_name
@@ -34,11 +54,15 @@ pub async fn check_player_access(
         return Err(Status::invalid_argument("Player name is empty"));
     }
 
-    // Validate player_ipv4
-    if player_ipv4.is_empty() || player_ipv4.parse::<Ipv4Addr>().is_err() {
-        warn!("[gRPC::CheckPlayerAccess] Player IPv4 is empty or invalid");
-        return Err(Status::invalid_argument("Player IPv4 is empty or invalid"));
-    }
+    // Validate player_ip
+    let Some(player_addr) = ip::parse(&raw_player_ip) else {
+        warn!("[gRPC::CheckPlayerAccess] Player IP is empty or invalid");
+        return Err(Status::invalid_argument("Player IP is empty or invalid"));
+    };
+    let player_ip = player_addr.to_string();
+
+    // Validate player_uuid (optional for older proxies)
+    let player_uuid = parse_player_uuid(&access_request.player_uuid)?;
 
     // Validate proxy_id
     if proxy_id.is_empty() {
@@ -53,8 +77,16 @@ pub async fn check_player_access(
     }
 
     // Get minecraft_proxies.discord_guild_id
-    let discord_guild_id = sqlx::query!(
-        "SELECT discord_guild_id FROM minecraft_proxies WHERE id = ?1",
+    let proxy = sqlx::query!(
+        r#"SELECT
+            minecraft_proxies.discord_guild_id,
+            minecraft_proxies.require_ip_match,
+            minecraft_proxies.registration_mode,
+            discord_guilds.deny_timed_out AS "deny_timed_out?",
+            discord_guilds.deny_pending AS "deny_pending?"
+        FROM minecraft_proxies
+        LEFT JOIN discord_guilds ON minecraft_proxies.discord_guild_id = discord_guilds.id
+        WHERE minecraft_proxies.id = ?1"#,
         proxy_id
     )
     .fetch_one(&state.data.db)
@@ -66,20 +98,24 @@ pub async fn check_player_access(
         );
 
         Status::not_found("Proxy not registered")
-    })?
-    .discord_guild_id
-    .ok_or_else(|| {
+    })?;
+
+    let Some(discord_guild_id) = proxy.discord_guild_id.map(|id| id as u64) else {
         warn!(
             "[gRPC::CheckPlayerAccess] Proxy `{}` has no discord_guild_id",
             proxy_id
         );
 
-        Status::not_found("Proxy's guild not registered")
-    })? as u64;
+        return Ok(denied(DenialReason::GuildNotConfigured, &locale));
+    };
+    let restrictions = MemberRestrictions {
+        deny_timed_out: proxy.deny_timed_out.unwrap_or(false),
+        deny_pending: proxy.deny_pending.unwrap_or(false),
+    };
 
-    // Get minecraft_servers.discord_role_id
-    let (server_type, discord_role_id) = sqlx::query!(
-        "SELECT server_type, discord_role_id FROM minecraft_servers WHERE proxy_id = ?1 AND server_name = ?2",
+    // Get minecraft_servers.server_type
+    let (server_id, server_type, role_match_mode, fail_policy) = sqlx::query!(
+        "SELECT id, server_type, role_match_mode, fail_policy FROM minecraft_servers WHERE proxy_id = ?1 AND server_name = ?2",
         proxy_id,
         server_name
     )
@@ -92,70 +128,371 @@ pub async fn check_player_access(
         );
 
         Status::not_found("Server not registered")
-    }).map(|record| (record.server_type.map(|id| id as u64), record.discord_role_id.map(|id| id as u64)))?;
+    }).map(|record| (record.id, record.server_type.map(|id| id as u64), record.role_match_mode, record.fail_policy))?;
 
-    if server_type.is_none() {
+    let Some(server_type) = server_type else {
         warn!(
             "[gRPC::CheckPlayerAccess] Server `{}` has no server_type configured",
             server_name
         );
 
-        return Err(Status::not_found("Server type not configured"));
-    }
+        return Ok(denied(DenialReason::GuildNotConfigured, &locale));
+    };
+    let role_match_mode = RoleMatchMode::from_i64(role_match_mode).unwrap_or(RoleMatchMode::Any);
+    let fail_policy_target = FailPolicyTarget {
+        server_id,
+        server_type,
+        fail_policy: FailPolicy::from_i64(fail_policy).unwrap_or(FailPolicy::Closed),
+    };
+
+    // Get minecraft_server_roles.discord_role_id
+    let discord_role_ids: Vec<u64> = sqlx::query!(
+        "SELECT discord_role_id FROM minecraft_server_roles WHERE server_id = ?1",
+        server_id
+    )
+    .fetch_all(&state.data.db)
+    .await
+    .map_err(|e| {
+        warn!(
+            "[gRPC::CheckPlayerAccess] Failed to fetch roles of server `{}`: {}",
+            server_name, e
+        );
 
-    let server_type = server_type.unwrap();
+        Status::internal("Database error")
+    })?
+    .into_iter()
+    .map(|record| record.discord_role_id as u64)
+    .collect();
 
     info!(
-        "[gRPC::CheckPlayerAccess] Server `{}` has type `{}` and role `{:?}`",
-        server_name, server_type, discord_role_id
+        "[gRPC::CheckPlayerAccess] Server `{}` has type `{}` and roles `{:?}` ({:?})",
+        server_name, server_type, discord_role_ids, role_match_mode
     );
 
-    // Check if role is configured for non-lobby servers
-    if discord_role_id.is_none() && server_type != MinecraftServerType::Lobby as u64 {
+    // Check if roles are configured for non-lobby servers
+    if discord_role_ids.is_empty() && server_type != MinecraftServerType::Lobby as u64 {
         warn!(
             "[gRPC::CheckPlayerAccess] Server `{}` has no discord_role_id configured",
             server_name
         );
 
-        return Err(Status::not_found("Server role not configured"));
+        return Ok(denied(DenialReason::GuildNotConfigured, &locale));
     }
 
-    // Lookup the player in the database
-    // get discord_id from player_name and player_ipv4
-    match sqlx::query!(
-        "SELECT minecraft_users.discord_user_id 
-        FROM minecraft_users 
-        WHERE minecraft_users.player_name = ?1 AND minecraft_users.player_ipv4 = ?2",
-        player_name,
-        player_ipv4
+    // Lookup the player in the database (UUID identity, or name + IP for older proxies)
+    let player =
+        get_or_create_player(state, player_uuid.as_deref(), &player_name, player_addr).await?;
+
+    let Some(discord_user_id) = player.discord_user_id else {
+        let registration_mode = proxy
+            .registration_mode
+            .and_then(RegistrationMode::from_i64)
+            .unwrap_or(config::get_config().registration_mode);
+
+        return match registration_mode {
+            RegistrationMode::OAuth if config::get_config().is_oauth_configured() => {
+                require_registration(state, player.id, &player_ip).await
+            }
+            // OAuth can't be used without its configuration, fall back to in-game codes
+            RegistrationMode::OAuth | RegistrationMode::Code => {
+                require_verification(state, player.id, &player_ip).await
+            }
+        };
+    };
+
+    // The IP is a secondary factor for older proxies (name + IP identity) or when the proxy requires it
+    if player.player_ip != player_ip {
+        let trust = if player_uuid.is_none() || proxy.require_ip_match {
+            trusted_ips::get_ip_trust(&state.data.db, player.id, &player.player_ip, player_addr)
+                .await
+        } else {
+            IpTrust::Trusted
+        };
+
+        match trust {
+            IpTrust::Trusted => {
+                update_player_ip(state, player.id, &player_name, &player_ip).await;
+            }
+            IpTrust::Pending => {
+                info!(
+                    "[gRPC::CheckPlayerAccess] Player `{}` IP {} is pending approval",
+                    player_name, player_ip
+                );
+
+                return Ok(Response::new(PlayerAccessResponse {
+                    status: AccessStatus::PendingApproval as i32,
+                    authentication_url: None,
+                    expires_in: None,
+                    verification_code: None,
+                    denial_reason: None,
+                    message: None,
+                }));
+            }
+            IpTrust::Denied => {
+                info!(
+                    "[gRPC::CheckPlayerAccess] Player `{}` IP {} was denied - Access denied",
+                    player_name, player_ip
+                );
+
+                return Ok(denied(DenialReason::IpDenied, &locale));
+            }
+            IpTrust::Unknown => {
+                return request_ip_approval(
+                    state,
+                    player.id,
+                    discord_user_id as u64,
+                    &player_name,
+                    &player_ip,
+                )
+                .await;
+            }
+        }
+    }
+
+    // Deny rules take precedence over the allow rules
+    match is_denied(state, server_id, discord_user_id as u64, discord_guild_id).await? {
+        DenialCheck::Denied => {
+            info!(
+                "[gRPC::CheckPlayerAccess] Player `{}` is denied on server `{}` - Access denied",
+                player_name, server_name
+            );
+
+            return Ok(denied(DenialReason::Banned, &locale));
+        }
+        // No fail policy may let a possibly denied member in
+        DenialCheck::Unknown => {
+            info!(
+                "[gRPC::CheckPlayerAccess] Discord is unavailable and server `{}` has role denials, denying player `{}`",
+                server_name, player_name
+            );
+
+            return Ok(denied(DenialReason::DiscordUnavailable, &locale));
+        }
+        DenialCheck::NotDenied => {}
+    }
+
+    // Timed access grants are honored regardless of roles, but not over the guild's restrictions
+    if has_active_grant(state, server_id, discord_user_id as u64).await? {
+        // Grants only hold while the player is still a member of the guild
+        let member = match state
+            .data
+            .member_cache
+            .get_or_fetch(
+                &state.ctx.http,
+                serenity::GuildId::new(discord_guild_id),
+                serenity::UserId::new(discord_user_id as u64),
+            )
+            .await
+        {
+            Ok(member) => member,
+            Err(e) => {
+                warn!(
+                    "[gRPC::CheckPlayerAccess] Failed to fetch granted player `{}` in guild {}: {}",
+                    player_name, discord_guild_id, e
+                );
+
+                return match member_error_reason(&e) {
+                    DenialReason::DiscordUnavailable => {
+                        fail_policy::on_discord_unavailable(
+                            state,
+                            &fail_policy_target,
+                            discord_user_id as u64,
+                            &locale,
+                        )
+                        .await
+                    }
+                    reason => Ok(denied(reason, &locale)),
+                };
+            }
+        };
+
+        if let Some(reason) = restrictions.check(&member) {
+            info!(
+                "[gRPC::CheckPlayerAccess] Player `{}` has an access grant but is restricted ({:?}) - Access denied",
+                player_name, reason
+            );
+
+            return Ok(denied(reason, &locale));
+        }
+
+        info!(
+            "[gRPC::CheckPlayerAccess] Player `{}` has an access grant on server `{}` - Access granted",
+            player_name, server_name
+        );
+
+        return Ok(Response::new(PlayerAccessResponse {
+            status: AccessStatus::Allowed as i32,
+            authentication_url: None,
+            expires_in: None,
+            verification_code: None,
+            denial_reason: None,
+            message: None,
+        }));
+    }
+
+    if server_type == MinecraftServerType::Lobby as u64 {
+        check_is_guild_member(
+            state,
+            discord_user_id as u64,
+            discord_guild_id,
+            &restrictions,
+            &fail_policy_target,
+            &locale,
+        )
+        .await
+    } else {
+        check_user_has_roles(
+            state,
+            discord_user_id as u64,
+            discord_guild_id,
+            &discord_role_ids,
+            role_match_mode,
+            &restrictions,
+            &fail_policy_target,
+            &locale,
+        )
+        .await
+    }
+}
+
+/// Guild settings denying members regardless of their roles
+struct MemberRestrictions {
+    deny_timed_out: bool,
+    deny_pending: bool,
+}
+
+impl MemberRestrictions {
+    /// Returns why the guild settings deny the member, if they do
+    fn check(&self, member: &serenity::Member) -> Option<DenialReason> {
+        let timed_out = member
+            .communication_disabled_until
+            .is_some_and(|until| until > serenity::Timestamp::now());
+
+        if self.deny_timed_out && timed_out {
+            Some(DenialReason::TimedOut)
+        } else if self.deny_pending && member.pending {
+            Some(DenialReason::PendingScreening)
+        } else {
+            None
+        }
+    }
+}
+
+/// Outcome of the deny rules of a server for a user
+enum DenialCheck {
+    NotDenied,
+    Denied,
+    /// The server has role denials but the member's roles couldn't be fetched
+    Unknown,
+}
+
+/// Checks whether the user, or one of their roles, is denied on the server
+async fn is_denied(
+    state: &GrpcServiceState,
+    server_id: i64,
+    discord_user_id: u64,
+    discord_guild_id: u64,
+) -> Result<DenialCheck, Status> {
+    let denials = sqlx::query!(
+        "SELECT discord_user_id, discord_role_id FROM minecraft_server_denials WHERE server_id = ?1",
+        server_id
     )
-    .fetch_one(&state.data.db)
+    .fetch_all(&state.data.db)
     .await
+    .map_err(|e| {
+        warn!(
+            "[gRPC::CheckPlayerAccess] Failed to fetch denials of server {}: {}",
+            server_id, e
+        );
+
+        Status::internal("Database error")
+    })?;
+
+    if denials
+        .iter()
+        .any(|denial| denial.discord_user_id == Some(discord_user_id as i64))
     {
-        Ok(record) => match record.discord_user_id {
-            None => require_registration(player_name, player_ipv4, state).await,
-            Some(discord_user_id) => {
-                if server_type == MinecraftServerType::Lobby as u64 {
-                    check_is_guild_member(state, discord_user_id as u64, discord_guild_id).await
-                } else {
-                    check_user_has_role(
-                        state,
-                        discord_user_id as u64,
-                        discord_guild_id,
-                        discord_role_id.unwrap(),
-                    )
-                    .await
-                }
-            }
-        },
-        Err(_e) => require_registration(player_name, player_ipv4, state).await,
+        return Ok(DenialCheck::Denied);
+    }
+
+    let denied_role_ids: Vec<serenity::RoleId> = denials
+        .iter()
+        .filter_map(|denial| denial.discord_role_id)
+        .map(|id| serenity::RoleId::new(id as u64))
+        .collect();
+
+    if denied_role_ids.is_empty() {
+        return Ok(DenialCheck::NotDenied);
+    }
+
+    let member = match state
+        .data
+        .member_cache
+        .get_or_fetch(
+            &state.ctx.http,
+            serenity::GuildId::new(discord_guild_id),
+            serenity::UserId::new(discord_user_id),
+        )
+        .await
+    {
+        Ok(member) => member,
+        // Outages leave the member's roles unknown, the fail policy can't vouch for them
+        Err(e) if matches!(member_error_reason(&e), DenialReason::DiscordUnavailable) => {
+            return Ok(DenialCheck::Unknown);
+        }
+        // Users that aren't members are rejected by the allow rules anyway
+        Err(_) => return Ok(DenialCheck::NotDenied),
+    };
+
+    if denied_role_ids
+        .iter()
+        .any(|role_id| member.roles.contains(role_id))
+    {
+        Ok(DenialCheck::Denied)
+    } else {
+        Ok(DenialCheck::NotDenied)
     }
 }
 
+/// Returns whether the user has an unexpired access grant on the server
+async fn has_active_grant(
+    state: &GrpcServiceState,
+    server_id: i64,
+    discord_user_id: u64,
+) -> Result<bool, Status> {
+    let discord_user_id = discord_user_id as i64;
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    sqlx::query!(
+        "SELECT id FROM minecraft_access_grants
+        WHERE server_id = ?1 AND discord_user_id = ?2 AND expires_at > ?3",
+        server_id,
+        discord_user_id,
+        now_ms
+    )
+    .fetch_optional(&state.data.db)
+    .await
+    .map(|record| record.is_some())
+    .map_err(|e| {
+        warn!(
+            "[gRPC::CheckPlayerAccess] Failed to fetch access grants of server {}: {}",
+            server_id, e
+        );
+
+        Status::internal("Database error")
+    })
+}
+
 async fn check_is_guild_member(
     state: &GrpcServiceState,
     discord_user_id: u64,
     discord_guild_id: u64,
+    restrictions: &MemberRestrictions,
+    fail_policy_target: &FailPolicyTarget,
+    locale: &str,
 ) -> Result<Response<PlayerAccessResponse>, Status> {
     // Convert i64 IDs to serenity types
     let user_id = serenity::UserId::new(discord_user_id);
@@ -166,18 +503,37 @@ async fn check_is_guild_member(
         user_id, guild_id
     );
 
-    // Fetch the member from Discord API
-    match state.ctx.http.get_member(guild_id, user_id).await {
-        Ok(_) => {
+    // Fetch the member from the cache (falls back to the Discord API)
+    match state
+        .data
+        .member_cache
+        .get_or_fetch(&state.ctx.http, guild_id, user_id)
+        .await
+    {
+        Ok(member) => {
+            if let Some(reason) = restrictions.check(&member) {
+                info!(
+                    "[gRPC::CheckPlayerAccess] User {} is restricted in guild {} ({:?}) - Access denied",
+                    user_id, guild_id, reason
+                );
+
+                return Ok(denied(reason, locale));
+            }
+
             info!(
                 "[gRPC::CheckPlayerAccess] User {} is a member of guild {} - Access granted",
                 user_id, guild_id
             );
 
+            fail_policy::record_allowed(state, fail_policy_target.server_id, discord_user_id).await;
+
             Ok(Response::new(PlayerAccessResponse {
                 status: AccessStatus::Allowed as i32,
                 authentication_url: None,
                 expires_in: None,
+                verification_code: None,
+                denial_reason: None,
+                message: None,
             }))
         }
         Err(e) => {
@@ -186,57 +542,96 @@ async fn check_is_guild_member(
                 user_id, guild_id, e
             );
 
-            Ok(Response::new(PlayerAccessResponse {
-                status: AccessStatus::Prohibited as i32,
-                authentication_url: None,
-                expires_in: None,
-            }))
+            match member_error_reason(&e) {
+                DenialReason::DiscordUnavailable => {
+                    fail_policy::on_discord_unavailable(
+                        state,
+                        fail_policy_target,
+                        discord_user_id,
+                        locale,
+                    )
+                    .await
+                }
+                reason => Ok(denied(reason, locale)),
+            }
         }
     }
 }
 
-async fn check_user_has_role(
+async fn check_user_has_roles(
     state: &GrpcServiceState,
     discord_user_id: u64,
     discord_guild_id: u64,
-    discord_role_id: u64,
+    discord_role_ids: &[u64],
+    role_match_mode: RoleMatchMode,
+    restrictions: &MemberRestrictions,
+    fail_policy_target: &FailPolicyTarget,
+    locale: &str,
 ) -> Result<Response<PlayerAccessResponse>, Status> {
     // Convert i64 IDs to serenity types
     let user_id = serenity::UserId::new(discord_user_id);
     let guild_id = serenity::GuildId::new(discord_guild_id);
-    let role_id = serenity::RoleId::new(discord_role_id);
+    let role_ids: Vec<serenity::RoleId> = discord_role_ids
+        .iter()
+        .map(|id| serenity::RoleId::new(*id))
+        .collect();
 
     info!(
-        "[gRPC::CheckPlayerAccess] Checking if user {} has role {} in guild {}",
-        user_id, role_id, guild_id
+        "[gRPC::CheckPlayerAccess] Checking if user {} has {:?} of roles {:?} in guild {}",
+        user_id, role_match_mode, role_ids, guild_id
     );
 
-    // Fetch the member from Discord API
-    match state.ctx.http.get_member(guild_id, user_id).await {
+    // Fetch the member from the cache (falls back to the Discord API)
+    match state
+        .data
+        .member_cache
+        .get_or_fetch(&state.ctx.http, guild_id, user_id)
+        .await
+    {
         Ok(member) => {
-            // Check if the member has the required role
-            if member.roles.contains(&role_id) {
+            if let Some(reason) = restrictions.check(&member) {
+                info!(
+                    "[gRPC::CheckPlayerAccess] User {} is restricted in guild {} ({:?}) - Access denied",
+                    user_id, guild_id, reason
+                );
+
+                return Ok(denied(reason, locale));
+            }
+
+            // Check if the member has the required roles
+            let has_roles = match role_match_mode {
+                RoleMatchMode::Any => role_ids
+                    .iter()
+                    .any(|role_id| member.roles.contains(role_id)),
+                RoleMatchMode::All => role_ids
+                    .iter()
+                    .all(|role_id| member.roles.contains(role_id)),
+            };
+
+            if has_roles {
                 info!(
-                    "[gRPC::CheckPlayerAccess] User {} has role {} in guild {} - Access granted",
-                    user_id, role_id, guild_id
+                    "[gRPC::CheckPlayerAccess] User {} has the required roles in guild {} - Access granted",
+                    user_id, guild_id
                 );
 
+                fail_policy::record_allowed(state, fail_policy_target.server_id, discord_user_id)
+                    .await;
+
                 Ok(Response::new(PlayerAccessResponse {
                     status: AccessStatus::Allowed as i32,
                     authentication_url: None,
                     expires_in: None,
+                    verification_code: None,
+                    denial_reason: None,
+                    message: None,
                 }))
             } else {
                 info!(
-                    "[gRPC::CheckPlayerAccess] User {} does not have role {} in guild {} - Access denied",
-                    user_id, role_id, guild_id
+                    "[gRPC::CheckPlayerAccess] User {} does not have the required roles in guild {} - Access denied",
+                    user_id, guild_id
                 );
 
-                Ok(Response::new(PlayerAccessResponse {
-                    status: AccessStatus::Prohibited as i32,
-                    authentication_url: None,
-                    expires_in: None,
-                }))
+                Ok(denied(DenialReason::MissingRole, locale))
             }
         }
         Err(e) => {
@@ -245,64 +640,111 @@ async fn check_user_has_role(
                 user_id, guild_id, e
             );
 
-            Ok(Response::new(PlayerAccessResponse {
-                status: AccessStatus::Prohibited as i32,
-                authentication_url: None,
-                expires_in: None,
-            }))
+            match member_error_reason(&e) {
+                DenialReason::DiscordUnavailable => {
+                    fail_policy::on_discord_unavailable(
+                        state,
+                        fail_policy_target,
+                        discord_user_id,
+                        locale,
+                    )
+                    .await
+                }
+                reason => Ok(denied(reason, locale)),
+            }
         }
     }
 }
 
-async fn require_registration(
-    player_name: String,
-    player_ipv4: String,
+/// Asks the linked Discord user to approve a new IP through a direct message
+async fn request_ip_approval(
     state: &GrpcServiceState,
+    minecraft_user_id: i64,
+    discord_user_id: u64,
+    player_name: &str,
+    player_ip: &str,
 ) -> Result<Response<PlayerAccessResponse>, Status> {
     info!(
-        "[gRPC::CheckPlayerAccess] Player `{}` ({}) registration required",
-        player_name, player_ipv4
+        "[gRPC::CheckPlayerAccess] Player `{}` joined from new IP {}, requesting approval",
+        player_name, player_ip
     );
 
-    let mut minecraft_user_id: i64 = crate::utils::snowflake::generate_id();
-
-    // Insert or ignore if already exists
-    let _ = sqlx::query!(
-        "INSERT OR IGNORE INTO minecraft_users (id, player_name, player_ipv4) VALUES (?1, ?2, ?3)",
+    let trusted_ip_id = trusted_ips::set_ip_status(
+        &state.data.db,
         minecraft_user_id,
-        player_name,
-        player_ipv4
-    )
-    .execute(&state.data.db)
-    .await;
-
-    // Now fetch the id (whether it was just inserted or already existed)
-    minecraft_user_id = sqlx::query!(
-        "SELECT id FROM minecraft_users WHERE player_name = ?1 AND player_ipv4 = ?2",
-        player_name,
-        player_ipv4
+        player_ip,
+        TrustedIpStatus::Pending,
     )
-    .fetch_one(&state.data.db)
     .await
     .map_err(|e| {
         warn!(
-            "[gRPC::CheckPlayerAccess] Failed to fetch minecraft_user id for `{}` ({}): {}",
-            player_name, player_ipv4, e
+            "[gRPC::CheckPlayerAccess] Failed to insert trusted IP for user id {}: {}",
+            minecraft_user_id, e
         );
 
         Status::internal("Database error")
-    })
-    .unwrap()
-    .id;
+    })?;
+
+    let embed = embed::warn()
+        .title("<:minecraft:1435794853517721722>  New Minecraft login")
+        .description(format!(
+            "Your Minecraft account `{}` is trying to join from a new IP address (`{}`).\n\nWas this you?",
+            player_name, player_ip
+        ));
+
+    let buttons = serenity::CreateActionRow::Buttons(vec![
+        serenity::CreateButton::new(format!("trusted_ip:approve:{}", trusted_ip_id))
+            .label("Approve")
+            .style(serenity::ButtonStyle::Success),
+        serenity::CreateButton::new(format!("trusted_ip:deny:{}", trusted_ip_id))
+            .label("Deny")
+            .style(serenity::ButtonStyle::Danger),
+    ]);
+
+    if let Err(e) = serenity::UserId::new(discord_user_id)
+        .direct_message(
+            state.ctx.as_ref(),
+            serenity::CreateMessage::new()
+                .embed(embed)
+                .components(vec![buttons]),
+        )
+        .await
+    {
+        warn!(
+            "[gRPC::CheckPlayerAccess] Failed to send IP approval request to user {}: {}",
+            discord_user_id, e
+        );
+    }
+
+    Ok(Response::new(PlayerAccessResponse {
+        status: AccessStatus::PendingApproval as i32,
+        authentication_url: None,
+        expires_in: Some((trusted_ips::APPROVAL_EXPIRY_MS / 1000) as u32),
+        verification_code: None,
+        denial_reason: None,
+        message: None,
+    }))
+}
+
+async fn require_registration(
+    state: &GrpcServiceState,
+    minecraft_user_id: i64,
+    player_ip: &str,
+) -> Result<Response<PlayerAccessResponse>, Status> {
+    info!(
+        "[gRPC::CheckPlayerAccess] Minecraft user {} ({}) registration required",
+        minecraft_user_id, player_ip
+    );
 
     let minecraft_registration_id: i64 = crate::utils::snowflake::generate_id();
     let state_token = Uuid::new_v4().to_string();
 
     let _ = sqlx::query!(
-            "INSERT OR REPLACE INTO minecraft_registrations (id, state_token, minecraft_user_id) VALUES (?1, ?2, ?3)",
+            "INSERT OR REPLACE INTO minecraft_registrations (id, state_token, minecraft_user_id, player_ip) VALUES (?1, ?2, ?3, ?4)",
             minecraft_registration_id,
             state_token,
-            minecraft_user_id
+            minecraft_user_id,
+            player_ip
         )
         .execute(&state.data.db)
         .await.map_err(|e| {
@@ -337,6 +779,67 @@ async fn require_registration(
     Ok(Response::new(PlayerAccessResponse {
         status: AccessStatus::RequiresSignup as i32,
         authentication_url: Some(authentication_url.to_string()),
-        expires_in: Some(300),
+        expires_in: Some(config::get_config().registration_ttl_secs as u32),
+        verification_code: None,
+        denial_reason: None,
+        message: None,
     }))
 }
+
+/// Returns a code the player types with `/minecraft verify` on Discord
+async fn require_verification(
+    state: &GrpcServiceState,
+    minecraft_user_id: i64,
+    player_ip: &str,
+) -> Result<Response<PlayerAccessResponse>, Status> {
+    info!(
+        "[gRPC::CheckPlayerAccess] Minecraft user {} ({}) verification required",
+        minecraft_user_id, player_ip
+    );
+
+    // Replace any previous registration of this user
+    let _ = sqlx::query!(
+        "DELETE FROM minecraft_registrations WHERE minecraft_user_id = ?1",
+        minecraft_user_id
+    )
+    .execute(&state.data.db)
+    .await;
+
+    // Codes are short, retry on the unlikely collision with another registration
+    for _ in 0..5 {
+        let minecraft_registration_id: i64 = crate::utils::snowflake::generate_id();
+        let state_token = Uuid::new_v4().to_string();
+        let verification_code = linking::generate_code();
+
+        match sqlx::query!(
+            "INSERT INTO minecraft_registrations (id, state_token, minecraft_user_id, player_ip, verification_code) VALUES (?1, ?2, ?3, ?4, ?5)",
+            minecraft_registration_id,
+            state_token,
+            minecraft_user_id,
+            player_ip,
+            verification_code
+        )
+        .execute(&state.data.db)
+        .await
+        {
+            Ok(_) => {
+                return Ok(Response::new(PlayerAccessResponse {
+                    status: AccessStatus::RequiresVerification as i32,
+                    authentication_url: None,
+                    expires_in: Some(config::get_config().registration_ttl_secs as u32),
+                    verification_code: Some(verification_code),
+                    denial_reason: None,
+                    message: None,
+                }));
+            }
+            Err(e) => {
+                warn!(
+                    "[gRPC::CheckPlayerAccess] Failed to insert minecraft_registration for user id {}: {}",
+                    minecraft_user_id, e
+                );
+            }
+        }
+    }
+
+    Err(Status::internal("Database error"))
+}
@@ -0,0 +1,344 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, info, warn};
+
+use crate::grpc::auth::{self, authorize_proxy};
+use crate::grpc::minecraft_bridge::{
+    HeartbeatAck, PlayerJoin, ProxyMessage, ServerEvent, SessionAccepted, TwigMessage,
+    proxy_message, twig_message,
+};
+use crate::grpc::session::OnlinePlayer;
+use crate::grpc::{EventFilter, GrpcServiceState, forward_events};
+use crate::utils::{chat, player_sessions, snowflake};
+
+/// How often proxies must send a heartbeat
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A session without any message for this long is closed
+const SESSION_TIMEOUT: Duration = Duration::from_secs(HEARTBEAT_INTERVAL.as_secs() * 3);
+
+type ConnectStream =
+    std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<TwigMessage, Status>> + Send>>;
+
+impl From<ServerEvent> for TwigMessage {
+    fn from(event: ServerEvent) -> Self {
+        TwigMessage {
+            payload: Some(twig_message::Payload::Event(event)),
+        }
+    }
+}
+
+/// Open a bidirectional session with a proxy
+///
+/// The first message must be a `ProxyHello`. Twig then streams the proxy's
+/// events while tracking its heartbeats and players in `Data::proxy_sessions`.
+pub async fn connect(
+    state: &GrpcServiceState,
+    mut request: Request<Streaming<ProxyMessage>>,
+) -> Result<Response<ConnectStream>, Status> {
+    // The first message identifies the proxy
+    let hello = match tokio::time::timeout(SESSION_TIMEOUT, request.get_mut().message()).await {
+        Ok(Ok(Some(ProxyMessage {
+            payload: Some(proxy_message::Payload::Hello(hello)),
+        }))) => hello,
+        Ok(Err(status)) => return Err(status),
+        Err(_) => {
+            warn!("[gRPC::Connect] No hello received before the timeout");
+            return Err(Status::deadline_exceeded("No hello received"));
+        }
+        _ => {
+            warn!("[gRPC::Connect] Session didn't start with a hello");
+            return Err(Status::invalid_argument(
+                "The first message must be a hello",
+            ));
+        }
+    };
+
+    authorize_proxy(&request, &hello.proxy_id)?;
+
+    let proxy_id = hello.proxy_id;
+
+    // Validate proxy_id
+    if proxy_id.is_empty() {
+        warn!("[gRPC::Connect] Received hello with empty proxy_id");
+        return Err(Status::invalid_argument("proxy_id is empty"));
+    }
+
+    // Proxies must register their servers first
+    sqlx::query!("SELECT id FROM minecraft_proxies WHERE id = ?1", proxy_id)
+        .fetch_optional(&state.data.db)
+        .await
+        .map_err(|e| {
+            warn!(
+                "[gRPC::Connect] Failed to fetch proxy `{}`: {}",
+                proxy_id, e
+            );
+
+            Status::internal("Database error")
+        })?
+        .ok_or_else(|| {
+            warn!("[gRPC::Connect] Proxy `{}` is not registered", proxy_id);
+            Status::not_found("Proxy not registered")
+        })?;
+
+    let (tx, rx) = mpsc::channel(100);
+    let _ = tx
+        .send(Ok(TwigMessage {
+            payload: Some(twig_message::Payload::Accepted(SessionAccepted {
+                heartbeat_interval: HEARTBEAT_INTERVAL.as_secs() as u32,
+            })),
+        }))
+        .await;
+
+    let filter = EventFilter {
+        proxy_id: proxy_id.clone(),
+        event_types: hello.event_types,
+        accepts_commands: true,
+        key_hash: auth::key_hash(&request),
+    };
+    let forwarder = forward_events(state, filter, hello.last_seen_sequence, tx.clone()).await?;
+
+    let session_id = snowflake::generate_id();
+    state.data.proxy_sessions.open(&proxy_id, session_id);
+
+    // Sessions left open by a previous connection are resumed from the hello,
+    // so no player event of this connection can predate it
+    let connected_at = player_sessions::now_ms();
+    if let Err(e) = player_sessions::end_all(&state.data.db, Some(&proxy_id), connected_at).await {
+        warn!(
+            "[gRPC::Connect] Failed to end the player sessions of proxy `{}`: {}",
+            proxy_id, e
+        );
+    }
+
+    for player in hello.online_players {
+        player_join(state, &proxy_id, session_id, connected_at, player).await;
+    }
+
+    let state = state.clone();
+    let mut inbound = request.into_inner();
+
+    tokio::spawn(async move {
+        loop {
+            let message = match tokio::time::timeout(SESSION_TIMEOUT, inbound.message()).await {
+                Ok(Ok(Some(message))) => message,
+                Ok(Ok(None)) => break, // Client closed the stream
+                Ok(Err(status)) => {
+                    warn!(
+                        "[gRPC::Connect] Session of proxy `{}` failed: {}",
+                        proxy_id, status
+                    );
+                    break;
+                }
+                Err(_) => {
+                    warn!(
+                        "[gRPC::Connect] Proxy `{}` missed its heartbeats, closing the session",
+                        proxy_id
+                    );
+                    break;
+                }
+            };
+
+            if !handle_message(&state, &proxy_id, session_id, connected_at, message, &tx).await {
+                break;
+            }
+        }
+
+        forwarder.abort();
+
+        // A replaced session leaves the players to the new one
+        if state.data.proxy_sessions.close(&proxy_id, session_id)
+            && let Err(e) =
+                player_sessions::end_all(&state.data.db, Some(&proxy_id), player_sessions::now_ms())
+                    .await
+        {
+            warn!(
+                "[gRPC::Connect] Failed to end the player sessions of proxy `{}`: {}",
+                proxy_id, e
+            );
+        }
+    });
+
+    Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+}
+
+/// Applies a proxy message to its session, returns `false` once the session was replaced
+async fn handle_message(
+    state: &GrpcServiceState,
+    proxy_id: &str,
+    session_id: i64,
+    connected_at: i64,
+    message: ProxyMessage,
+    tx: &mpsc::Sender<Result<TwigMessage, Status>>,
+) -> bool {
+    let sessions = &state.data.proxy_sessions;
+
+    match message.payload {
+        Some(proxy_message::Payload::Heartbeat(heartbeat)) => {
+            debug!(
+                "[gRPC::Connect] Heartbeat from proxy `{}` ({} players)",
+                proxy_id, heartbeat.online_players
+            );
+
+            if !sessions.heartbeat(proxy_id, session_id) {
+                return false;
+            }
+
+            let _ = tx
+                .send(Ok(TwigMessage {
+                    payload: Some(twig_message::Payload::HeartbeatAck(HeartbeatAck {
+                        timestamp: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap()
+                            .as_millis() as i64,
+                    })),
+                }))
+                .await;
+
+            true
+        }
+        Some(proxy_message::Payload::PlayerJoin(join)) => {
+            player_join(state, proxy_id, session_id, connected_at, join).await
+        }
+        Some(proxy_message::Payload::PlayerSwitch(switch)) => {
+            info!(
+                "[gRPC::Connect] Player `{}` moved from `{}` on proxy `{}`",
+                switch.player_name, switch.from_server, proxy_id
+            );
+
+            let join = PlayerJoin {
+                player_uuid: switch.player_uuid,
+                player_name: switch.player_name,
+                server_name: switch.to_server,
+                timestamp: switch.timestamp,
+            };
+            player_join(state, proxy_id, session_id, connected_at, join).await
+        }
+        Some(proxy_message::Payload::PlayerLeave(leave)) => {
+            info!(
+                "[gRPC::Connect] Player `{}` left proxy `{}`",
+                leave.player_name, proxy_id
+            );
+
+            if !sessions.player_leave(proxy_id, session_id, &leave.player_uuid) {
+                return false;
+            }
+
+            let ended_at = event_time(leave.timestamp, connected_at);
+            if let Err(e) =
+                player_sessions::end(&state.data.db, proxy_id, &leave.player_uuid, ended_at).await
+            {
+                warn!(
+                    "[gRPC::Connect] Failed to end the session of player `{}`: {}",
+                    leave.player_name, e
+                );
+            }
+
+            true
+        }
+        Some(proxy_message::Payload::CommandAck(ack)) => {
+            info!(
+                "[gRPC::Connect] Proxy `{}` acknowledged command {} (success: {})",
+                proxy_id, ack.command_id, ack.success
+            );
+
+            sessions.acknowledge(proxy_id, ack);
+            true
+        }
+        Some(proxy_message::Payload::ChatMessage(chat_message)) => {
+            debug!(
+                "[gRPC::Connect] Chat message from `{}` on `{}` (proxy `{}`)",
+                chat_message.player_name, chat_message.server_name, proxy_id
+            );
+
+            // Don't hold the session up while Discord answers
+            let state = state.clone();
+            let proxy_id = proxy_id.to_string();
+            tokio::spawn(async move {
+                if let Err(e) = chat::relay_to_discord(
+                    &state.ctx.http,
+                    &state.data.db,
+                    &proxy_id,
+                    &chat_message.server_name,
+                    &chat_message.player_uuid,
+                    &chat_message.player_name,
+                    &chat_message.message,
+                )
+                .await
+                {
+                    warn!(
+                        "[gRPC::Connect] Failed to relay the chat of server `{}`: {}",
+                        chat_message.server_name, e
+                    );
+                }
+            });
+
+            true
+        }
+        Some(proxy_message::Payload::Hello(_)) => {
+            warn!(
+                "[gRPC::Connect] Proxy `{}` sent a second hello, ignoring it",
+                proxy_id
+            );
+            true
+        }
+        None => true,
+    }
+}
+
+/// Records a player joining (or moving to) a server of the proxy, returns `false` once the session was replaced
+async fn player_join(
+    state: &GrpcServiceState,
+    proxy_id: &str,
+    session_id: i64,
+    connected_at: i64,
+    join: PlayerJoin,
+) -> bool {
+    info!(
+        "[gRPC::Connect] Player `{}` joined `{}` on proxy `{}`",
+        join.player_name, join.server_name, proxy_id
+    );
+
+    if !state.data.proxy_sessions.player_join(
+        proxy_id,
+        session_id,
+        join.player_uuid.clone(),
+        OnlinePlayer {
+            player_name: join.player_name.clone(),
+            server_name: join.server_name.clone(),
+        },
+    ) {
+        return false;
+    }
+
+    let started_at = event_time(join.timestamp, connected_at);
+    if let Err(e) = player_sessions::start(
+        &state.data.db,
+        proxy_id,
+        &join.player_uuid,
+        &join.server_name,
+        started_at,
+    )
+    .await
+    {
+        warn!(
+            "[gRPC::Connect] Failed to start the session of player `{}`: {}",
+            join.player_name, e
+        );
+    }
+
+    true
+}
+
+/// Returns the time of a player event, clamped between the connection start and now
+///
+/// Proxy clocks can't be trusted to not predate the connection or be in the future.
+fn event_time(timestamp: Option<i64>, connected_at: i64) -> i64 {
+    let now_ms = player_sessions::now_ms();
+    timestamp
+        .unwrap_or(now_ms)
+        .clamp(connected_at.min(now_ms), now_ms)
+}
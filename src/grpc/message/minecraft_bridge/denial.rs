@@ -0,0 +1,95 @@
+use poise::serenity_prelude::{self as serenity};
+use tonic::Response;
+
+use crate::grpc::minecraft_bridge::{AccessStatus, DenialReason, PlayerAccessResponse};
+use crate::utils::config;
+
+/// Builds a `PROHIBITED` response carrying the reason and a localized message
+pub fn denied(reason: DenialReason, locale: &str) -> Response<PlayerAccessResponse> {
+    Response::new(PlayerAccessResponse {
+        status: AccessStatus::Prohibited as i32,
+        authentication_url: None,
+        expires_in: None,
+        verification_code: None,
+        denial_reason: Some(reason as i32),
+        message: Some(message(reason, locale).to_string()),
+    })
+}
+
+/// Maps a failed member lookup to a denial reason
+///
+/// Discord answers 404 for users that aren't members of the guild, anything
+/// else means the member couldn't be checked.
+pub fn member_error_reason(e: &serenity::Error) -> DenialReason {
+    match e {
+        serenity::Error::Http(serenity::HttpError::UnsuccessfulRequest(response))
+            if response.status_code.as_u16() == 404 =>
+        {
+            DenialReason::NotGuildMember
+        }
+        _ => DenialReason::DiscordUnavailable,
+    }
+}
+
+/// Returns the kick message of a denial reason in the player's language
+///
+/// Minecraft locales look like `pt_br`; only the language part is used and
+/// unknown languages fall back to `DEFAULT_LOCALE`.
+fn message(reason: DenialReason, locale: &str) -> &'static str {
+    let language = locale
+        .split(['_', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let language = match language.as_str() {
+        "en" | "pt" => language,
+        _ => config::get_config().default_locale.to_lowercase(),
+    };
+
+    match language.as_str() {
+        "pt" => match reason {
+            DenialReason::Unspecified => "Você não tem permissão para entrar neste servidor.",
+            DenialReason::NotGuildMember => {
+                "Você precisa ser membro do nosso servidor do Discord para entrar."
+            }
+            DenialReason::MissingRole => {
+                "Você não tem o cargo do Discord necessário para entrar neste servidor."
+            }
+            DenialReason::Banned => "Você está banido deste servidor.",
+            DenialReason::GuildNotConfigured => {
+                "Este servidor ainda não foi configurado, contate um administrador."
+            }
+            DenialReason::DiscordUnavailable => {
+                "O Discord está indisponível no momento, tente novamente em alguns minutos."
+            }
+            DenialReason::IpDenied => "Um login a partir deste endereço IP foi negado no Discord.",
+            DenialReason::TimedOut => {
+                "Você está de castigo no nosso servidor do Discord e não pode jogar até ele acabar."
+            }
+            DenialReason::PendingScreening => {
+                "Aceite as regras do nosso servidor do Discord antes de entrar."
+            }
+        },
+        _ => match reason {
+            DenialReason::Unspecified => "You are not allowed to join this server.",
+            DenialReason::NotGuildMember => "You must be a member of our Discord server to join.",
+            DenialReason::MissingRole => {
+                "You don't have the Discord role required to join this server."
+            }
+            DenialReason::Banned => "You are banned from this server.",
+            DenialReason::GuildNotConfigured => {
+                "This server isn't configured yet, please contact an administrator."
+            }
+            DenialReason::DiscordUnavailable => {
+                "Discord is unavailable right now, please try again in a few minutes."
+            }
+            DenialReason::IpDenied => "A login from this IP address was denied on Discord.",
+            DenialReason::TimedOut => {
+                "You are timed out on our Discord server and can't play until it ends."
+            }
+            DenialReason::PendingScreening => {
+                "Please accept the rules of our Discord server before joining."
+            }
+        },
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tonic::{Response, Status};
+use tracing::{info, warn};
+
+use super::denial::denied;
+use crate::grpc::GrpcServiceState;
+use crate::grpc::minecraft_bridge::{AccessStatus, DenialReason, PlayerAccessResponse};
+use crate::utils::config;
+use crate::utils::minecraft::{FailPolicy, MinecraftServerType};
+
+/// Allowed accesses are only recorded again once the stored one is older than this
+///
+/// Keeps the access check from writing on every join, at the cost of trusting
+/// the last known good access for up to this much less than `FAIL_OPEN_GRACE`.
+const RECORD_GRANULARITY_MS: i64 = 60 * 60 * 1000;
+
+/// Server being joined, as needed to apply its fail policy
+pub struct FailPolicyTarget {
+    pub server_id: i64,
+    pub server_type: u64,
+    pub fail_policy: FailPolicy,
+}
+
+/// Remembers that the member was allowed on the server, for [`FailPolicy::LastKnownGood`]
+pub async fn record_allowed(state: &GrpcServiceState, server_id: i64, discord_user_id: u64) {
+    let discord_user_id = discord_user_id as i64;
+    let now_ms = now_ms();
+
+    // Reads are cheap, skip the write while the stored access is recent enough
+    let recorded = sqlx::query!(
+        "SELECT allowed_at FROM minecraft_last_allowed WHERE server_id = ?1 AND discord_user_id = ?2",
+        server_id,
+        discord_user_id
+    )
+    .fetch_optional(&state.data.db)
+    .await;
+
+    if let Ok(Some(record)) = &recorded
+        && now_ms - record.allowed_at < RECORD_GRANULARITY_MS
+    {
+        return;
+    }
+
+    if let Err(e) = sqlx::query!(
+        "INSERT OR REPLACE INTO minecraft_last_allowed (server_id, discord_user_id, allowed_at) VALUES (?1, ?2, ?3)",
+        server_id,
+        discord_user_id,
+        now_ms
+    )
+    .execute(&state.data.db)
+    .await
+    {
+        warn!(
+            "[gRPC::CheckPlayerAccess] Failed to record last allowed access of user {}: {}",
+            discord_user_id, e
+        );
+    }
+}
+
+/// Decides the access of a member that couldn't be checked because Discord is unreachable
+pub async fn on_discord_unavailable(
+    state: &GrpcServiceState,
+    target: &FailPolicyTarget,
+    discord_user_id: u64,
+    locale: &str,
+) -> Result<Response<PlayerAccessResponse>, Status> {
+    let allowed = match target.fail_policy {
+        FailPolicy::Closed => false,
+        FailPolicy::LobbyOnly => target.server_type == MinecraftServerType::Lobby as u64,
+        FailPolicy::LastKnownGood => {
+            let discord_user_id = discord_user_id as i64;
+            let grace_ms = (config::get_config().fail_open_grace_secs * 1000) as i64;
+
+            sqlx::query!(
+                "SELECT allowed_at FROM minecraft_last_allowed WHERE server_id = ?1 AND discord_user_id = ?2",
+                target.server_id,
+                discord_user_id
+            )
+            .fetch_optional(&state.data.db)
+            .await
+            .map_err(|e| {
+                warn!(
+                    "[gRPC::CheckPlayerAccess] Failed to fetch last allowed access of user {}: {}",
+                    discord_user_id, e
+                );
+
+                Status::internal("Database error")
+            })?
+            .is_some_and(|record| now_ms() - record.allowed_at <= grace_ms)
+        }
+    };
+
+    info!(
+        "[gRPC::CheckPlayerAccess] Discord is unavailable, {:?} policy {} user {} on server {}",
+        target.fail_policy,
+        if allowed { "allows" } else { "denies" },
+        discord_user_id,
+        target.server_id
+    );
+
+    if !allowed {
+        return Ok(denied(DenialReason::DiscordUnavailable, locale));
+    }
+
+    Ok(Response::new(PlayerAccessResponse {
+        status: AccessStatus::Allowed as i32,
+        authentication_url: None,
+        expires_in: None,
+        verification_code: None,
+        denial_reason: None,
+        message: None,
+    }))
+}
+
+/// Returns the current time in milliseconds since the Unix epoch
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
@@ -1,5 +1,12 @@
 mod check_player_access;
+mod connect;
+mod denial;
+mod fail_policy;
+mod player;
+mod redeem_link_code;
 mod register_proxy;
 
 pub use check_player_access::check_player_access;
+pub use connect::connect;
+pub use redeem_link_code::redeem_link_code;
 pub use register_proxy::register_proxy;
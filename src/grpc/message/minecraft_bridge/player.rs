@@ -0,0 +1,265 @@
+use std::net::IpAddr;
+use tonic::Status;
+use tracing::{info, warn};
+
+use uuid::Uuid;
+
+use crate::grpc::GrpcServiceState;
+use crate::utils::ip;
+use crate::utils::trusted_ips::{self, IpTrust};
+
+/// Minecraft account resolved from a proxy request
+pub struct MinecraftUser {
+    pub id: i64,
+    pub discord_user_id: Option<i64>,
+    pub player_ip: String,
+}
+
+/// Parses the player UUID sent by a proxy
+///
+/// # Returns
+/// The hyphenated UUID, or `None` if the proxy did not send one
+///
+/// # Errors
+/// Returns `invalid_argument` if the UUID is malformed
+pub fn parse_player_uuid(player_uuid: &str) -> Result<Option<String>, Status> {
+    if player_uuid.is_empty() {
+        return Ok(None);
+    }
+
+    match Uuid::parse_str(player_uuid) {
+        Ok(uuid) => Ok(Some(uuid.hyphenated().to_string())),
+        Err(_) => {
+            warn!("[gRPC::player] Player UUID is invalid");
+            Err(Status::invalid_argument("Player UUID is invalid"))
+        }
+    }
+}
+
+/// Resolves the player's Minecraft account, creating an unlinked one if needed
+///
+/// When the proxy reports a UUID it is the player's identity: name and IP
+/// changes are followed, and accounts created before UUIDs were reported are
+/// adopted by name + IP. Older proxies keep the name + IP identity.
+pub async fn get_or_create_player(
+    state: &GrpcServiceState,
+    player_uuid: Option<&str>,
+    player_name: &str,
+    player_addr: IpAddr,
+) -> Result<MinecraftUser, Status> {
+    let player_ip = player_addr.to_string();
+
+    let Some(player_uuid) = player_uuid else {
+        if let Some(player) = find_player_by_name(state, player_name, player_addr, false).await {
+            return Ok(player);
+        }
+
+        // Insert or ignore if already exists
+        let minecraft_user_id: i64 = crate::utils::snowflake::generate_id();
+        let _ = sqlx::query!(
+            "INSERT OR IGNORE INTO minecraft_users (id, player_name, player_ip) VALUES (?1, ?2, ?3)",
+            minecraft_user_id,
+            player_name,
+            player_ip
+        )
+        .execute(&state.data.db)
+        .await;
+
+        // Now fetch the row (whether it was just inserted or already existed)
+        return sqlx::query_as!(
+            MinecraftUser,
+            "SELECT id, discord_user_id, player_ip FROM minecraft_users
+            WHERE player_name = ?1 AND player_ip = ?2 AND player_uuid IS NULL",
+            player_name,
+            player_ip
+        )
+        .fetch_one(&state.data.db)
+        .await
+        .map_err(|e| {
+            warn!(
+                "[gRPC::player] Failed to fetch minecraft_user for `{}` ({}): {}",
+                player_name, player_ip, e
+            );
+
+            Status::internal("Database error")
+        });
+    };
+
+    if let Some(player) = sqlx::query_as!(
+        MinecraftUser,
+        "SELECT id, discord_user_id, player_ip FROM minecraft_users WHERE player_uuid = ?1",
+        player_uuid
+    )
+    .fetch_optional(&state.data.db)
+    .await
+    .map_err(|e| {
+        warn!(
+            "[gRPC::player] Failed to fetch minecraft_user for `{}`: {}",
+            player_uuid, e
+        );
+
+        Status::internal("Database error")
+    })? {
+        // Follow username changes
+        let _ = sqlx::query!(
+            "UPDATE minecraft_users SET player_name = ?1 WHERE id = ?2 AND player_name != ?1",
+            player_name,
+            player.id
+        )
+        .execute(&state.data.db)
+        .await;
+
+        return Ok(player);
+    }
+
+    // Adopt an account created before the proxy reported UUIDs, as long as
+    // a linked one is claimed from one of its trusted IPs
+    if let Some(player) = find_player_by_name(state, player_name, player_addr, true).await
+        && (player.discord_user_id.is_none()
+            || matches!(
+                trusted_ips::get_ip_trust(
+                    &state.data.db,
+                    player.id,
+                    &player.player_ip,
+                    player_addr
+                )
+                .await,
+                IpTrust::Trusted
+            ))
+    {
+        info!(
+            "[gRPC::player] Adopting legacy minecraft_user {} as `{}`",
+            player.id, player_uuid
+        );
+
+        sqlx::query!(
+            "UPDATE minecraft_users SET player_uuid = ?1 WHERE id = ?2",
+            player_uuid,
+            player.id
+        )
+        .execute(&state.data.db)
+        .await
+        .map_err(|e| {
+            warn!(
+                "[gRPC::player] Failed to set UUID of minecraft_user {}: {}",
+                player.id, e
+            );
+
+            Status::internal("Database error")
+        })?;
+
+        return Ok(player);
+    }
+
+    let minecraft_user_id: i64 = crate::utils::snowflake::generate_id();
+    sqlx::query!(
+        "INSERT INTO minecraft_users (id, player_uuid, player_name, player_ip) VALUES (?1, ?2, ?3, ?4)",
+        minecraft_user_id,
+        player_uuid,
+        player_name,
+        player_ip
+    )
+    .execute(&state.data.db)
+    .await
+    .map_err(|e| {
+        warn!(
+            "[gRPC::player] Failed to insert minecraft_user `{}`: {}",
+            player_uuid, e
+        );
+
+        Status::internal("Database error")
+    })?;
+
+    Ok(MinecraftUser {
+        id: minecraft_user_id,
+        discord_user_id: None,
+        player_ip,
+    })
+}
+
+/// Finds an account by player name
+///
+/// Linked accounts are preferred (exact IP first, then the same network, then
+/// any IP so a new IP can be approved), then an unlinked account with the exact IP.
+async fn find_player_by_name(
+    state: &GrpcServiceState,
+    player_name: &str,
+    player_addr: IpAddr,
+    legacy_only: bool,
+) -> Option<MinecraftUser> {
+    let player_ip = player_addr.to_string();
+    let records = sqlx::query_as!(
+        MinecraftUser,
+        "SELECT id, discord_user_id, player_ip
+        FROM minecraft_users
+        WHERE player_name = ?1 AND (?2 = FALSE OR player_uuid IS NULL)",
+        player_name,
+        legacy_only
+    )
+    .fetch_all(&state.data.db)
+    .await
+    .unwrap_or_default();
+
+    let linked = || {
+        records
+            .iter()
+            .filter(|record| record.discord_user_id.is_some())
+    };
+    let record = linked()
+        .find(|record| record.player_ip == player_ip)
+        .or_else(|| {
+            linked().find(|record| {
+                ip::parse(&record.player_ip)
+                    .is_some_and(|stored| ip::same_network(stored, player_addr))
+            })
+        })
+        .or_else(|| linked().next())
+        .or_else(|| records.iter().find(|record| record.player_ip == player_ip))?;
+
+    Some(MinecraftUser {
+        id: record.id,
+        discord_user_id: record.discord_user_id,
+        player_ip: record.player_ip.clone(),
+    })
+}
+
+/// Moves a linked account to the player's current IP
+///
+/// Unlinked legacy rows left behind for the new IP are removed first so the
+/// `(player_name, player_ip)` uniqueness constraint cannot be violated.
+pub async fn update_player_ip(
+    state: &GrpcServiceState,
+    minecraft_user_id: i64,
+    player_name: &str,
+    player_ip: &str,
+) {
+    info!(
+        "[gRPC::player] Player `{}` moved to {}",
+        player_name, player_ip
+    );
+
+    let _ = sqlx::query!(
+        "DELETE FROM minecraft_users
+        WHERE player_name = ?1 AND player_ip = ?2 AND player_uuid IS NULL
+            AND discord_user_id IS NULL AND id != ?3",
+        player_name,
+        player_ip,
+        minecraft_user_id
+    )
+    .execute(&state.data.db)
+    .await;
+
+    if let Err(e) = sqlx::query!(
+        "UPDATE minecraft_users SET player_ip = ?1 WHERE id = ?2",
+        player_ip,
+        minecraft_user_id
+    )
+    .execute(&state.data.db)
+    .await
+    {
+        warn!(
+            "[gRPC::player] Failed to update IP of minecraft_user {}: {}",
+            minecraft_user_id, e
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use super::player::{get_or_create_player, parse_player_uuid};
+use crate::grpc::GrpcServiceState;
+use crate::grpc::auth::authorize_proxy;
+use crate::grpc::minecraft_bridge::{LinkCodeRedemption, LinkCodeResponse};
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+use crate::utils::linking::{self, LinkError};
+use crate::utils::{ip, snowflake};
+
+/// Link the player's Minecraft account using a code generated on Discord
+pub async fn redeem_link_code(
+    state: &GrpcServiceState,
+    request: Request<LinkCodeRedemption>,
+) -> Result<Response<LinkCodeResponse>, Status> {
+    authorize_proxy(&request, &request.get_ref().proxy_id)?;
+
+    let redemption = request.into_inner();
+    let code = redemption.code.trim().to_uppercase();
+    let player_name = redemption.player_name;
+
+    info!(
+        "[gRPC::RedeemLinkCode] Player `{}` redeeming a link code on proxy `{}`",
+        player_name, redemption.proxy_id
+    );
+
+    // Validate player_name
+    if player_name.is_empty() {
+        warn!("[gRPC::RedeemLinkCode] Player name is empty");
+        return Err(Status::invalid_argument("Player name is empty"));
+    }
+
+    // Validate player_ip
+    let Some(player_addr) = ip::parse(&redemption.player_ip) else {
+        warn!("[gRPC::RedeemLinkCode] Player IP is empty or invalid");
+        return Err(Status::invalid_argument("Player IP is empty or invalid"));
+    };
+    let player_ip = player_addr.to_string();
+
+    // Validate player_uuid (optional for older proxies)
+    let player_uuid = parse_player_uuid(&redemption.player_uuid)?;
+
+    // Validate code
+    if code.is_empty() {
+        warn!("[gRPC::RedeemLinkCode] Link code is empty");
+        return Err(Status::invalid_argument("Link code is empty"));
+    }
+
+    let Some(link_code) = sqlx::query!(
+        "SELECT id, discord_user_id FROM discord_link_codes WHERE code = ?1",
+        code
+    )
+    .fetch_optional(&state.data.db)
+    .await
+    .map_err(|e| {
+        warn!("[gRPC::RedeemLinkCode] Failed to fetch link code: {}", e);
+        Status::internal("Database error")
+    })?
+    .filter(|link_code| snowflake::is_snowflake_recent(link_code.id, linking::CODE_EXPIRY_MS)) else {
+        warn!("[gRPC::RedeemLinkCode] Link code is invalid or expired");
+        return Err(Status::not_found("Link code is invalid or expired"));
+    };
+
+    let player =
+        get_or_create_player(state, player_uuid.as_deref(), &player_name, player_addr).await?;
+
+    match linking::link_minecraft_user(
+        &state.data.db,
+        player.id,
+        link_code.discord_user_id,
+        &player_ip,
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(LinkError::AccountLinkedElsewhere) | Err(LinkError::NameLinkedElsewhere) => {
+            return Err(Status::failed_precondition(
+                "Minecraft account is already linked to another Discord user",
+            ));
+        }
+        Err(LinkError::Database(e)) => {
+            warn!(
+                "[gRPC::RedeemLinkCode] Failed to link Minecraft user {}: {}",
+                player.id, e
+            );
+
+            return Err(Status::internal("Database error"));
+        }
+    }
+
+    // Link codes are single-use
+    let _ = sqlx::query!("DELETE FROM discord_link_codes WHERE id = ?1", link_code.id)
+        .execute(&state.data.db)
+        .await;
+
+    // The player is waiting on the proxy that redeemed the code
+    minecraft_bridge::authenticated::broadcast_event(
+        state.data.clone(),
+        player.id,
+        EventTarget::Proxies(vec![redemption.proxy_id]),
+    )
+    .await;
+
+    Ok(Response::new(LinkCodeResponse { success: true }))
+}
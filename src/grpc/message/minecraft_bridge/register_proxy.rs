@@ -1,16 +1,21 @@
 use std::collections::HashSet;
 use std::hash::Hash;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tonic::{Request, Response, Status};
 use tracing::{info, warn};
 
 use crate::grpc::GrpcServiceState;
+use crate::grpc::auth::authorize_proxy;
 use crate::grpc::minecraft_bridge::{ProxyRegistration, RegistrationResponse};
+use crate::utils::linking;
 
 /// Register a Minecraft proxy and store its server list
 pub async fn register_proxy(
     state: &GrpcServiceState,
     request: Request<ProxyRegistration>,
 ) -> Result<Response<RegistrationResponse>, Status> {
+    authorize_proxy(&request, &request.get_ref().proxy_id)?;
+
     let registration = request.into_inner();
     let proxy_id = registration.proxy_id.clone();
     let servers = registration.servers;
@@ -48,7 +53,7 @@ pub async fn register_proxy(
     // Build list of server names to keep
     let server_names: Vec<String> = servers.iter().map(|s| s.name.clone()).collect();
 
-    // Delete servers for this proxy that are not in the registration list
+    // Deactivate servers for this proxy that are not in the registration list
     // First, get all existing servers for this proxy
     let existing_servers = sqlx::query!(
         "SELECT server_name FROM minecraft_servers WHERE proxy_id = ?1",
@@ -58,11 +63,12 @@ pub async fn register_proxy(
     .await
     .unwrap_or_default();
 
-    // Delete servers that are not in the new registration list
+    // Servers missing from the new registration list keep their sessions and chat channel,
+    // they may only be temporarily removed from the proxy's configuration
     for existing in existing_servers {
         if !server_names.contains(&existing.server_name) {
             let _ = sqlx::query!(
-                "DELETE FROM minecraft_servers WHERE proxy_id = ?1 AND server_name = ?2",
+                "UPDATE minecraft_servers SET active = FALSE WHERE proxy_id = ?1 AND server_name = ?2",
                 proxy_id,
                 existing.server_name
             )
@@ -95,17 +101,28 @@ pub async fn register_proxy(
         .fetch_optional(&state.data.db)
         .await;
 
-        // Only insert if it doesn't exist
-        if existing.is_ok() && existing.unwrap().is_none() {
-            let id: i64 = crate::utils::snowflake::generate_id();
-            let _ = sqlx::query!(
-                "INSERT INTO minecraft_servers (id, proxy_id, server_name) VALUES (?1, ?2, ?3)",
-                id,
-                proxy_id,
-                server.name
-            )
-            .execute(&state.data.db)
-            .await;
+        // Only insert if it doesn't exist, otherwise reactivate it
+        match existing {
+            Ok(Some(existing)) => {
+                let _ = sqlx::query!(
+                    "UPDATE minecraft_servers SET active = TRUE WHERE id = ?1",
+                    existing.id
+                )
+                .execute(&state.data.db)
+                .await;
+            }
+            Ok(None) => {
+                let id: i64 = crate::utils::snowflake::generate_id();
+                let _ = sqlx::query!(
+                    "INSERT INTO minecraft_servers (id, proxy_id, server_name) VALUES (?1, ?2, ?3)",
+                    id,
+                    proxy_id,
+                    server.name
+                )
+                .execute(&state.data.db)
+                .await;
+            }
+            Err(_) => {}
         }
     }
 
@@ -114,7 +131,69 @@ pub async fn register_proxy(
         &proxy_id, i
     );
 
-    Ok(Response::new(RegistrationResponse { success: true }))
+    let claim_code = issue_claim_code(state, &proxy_id).await?;
+
+    Ok(Response::new(RegistrationResponse {
+        success: true,
+        claim_code,
+    }))
+}
+
+/// Issues a new claim code if the proxy isn't bound to a guild yet
+///
+/// The previous code is replaced, so only the code printed by the latest
+/// registration can be used with `/minecraft claim`, until it expires.
+async fn issue_claim_code(
+    state: &GrpcServiceState,
+    proxy_id: &str,
+) -> Result<Option<String>, Status> {
+    let claimed = sqlx::query!(
+        "SELECT discord_guild_id FROM minecraft_proxies WHERE id = ?1",
+        proxy_id
+    )
+    .fetch_one(&state.data.db)
+    .await
+    .map_err(|e| {
+        warn!(
+            "[gRPC::RegisterProxy] Failed to fetch proxy `{}`: {}",
+            proxy_id, e
+        );
+        Status::internal("Database error")
+    })?
+    .discord_guild_id
+    .is_some();
+
+    if claimed {
+        return Ok(None);
+    }
+
+    let claim_code = linking::generate_long_code();
+    let issued_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    sqlx::query!(
+        "UPDATE minecraft_proxies SET claim_code = ?1, claim_code_issued_at = ?2 WHERE id = ?3",
+        claim_code,
+        issued_at,
+        proxy_id
+    )
+    .execute(&state.data.db)
+    .await
+    .map_err(|e| {
+        warn!(
+            "[gRPC::RegisterProxy] Failed to store claim code of proxy `{}`: {}",
+            proxy_id, e
+        );
+        Status::internal("Database error")
+    })?;
+
+    info!(
+        "[gRPC::RegisterProxy] Proxy `{}` is unclaimed, issued a new claim code",
+        proxy_id
+    );
+
+    Ok(Some(claim_code))
 }
 
 /// Helper function to check if an iterator has unique elements
@@ -2,23 +2,38 @@ use poise::serenity_prelude as serenity;
 use std::net::SocketAddr;
 use std::pin::Pin;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
 use tokio_stream::{Stream, wrappers::ReceiverStream};
-use tonic::{Request, Response, Status, transport::Server};
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status, Streaming};
 use tracing::{info, warn};
 
+use crate::utils::config;
+
 // Include the generated protobuf code
 pub mod minecraft_bridge {
     tonic::include_proto!("minecraft_bridge");
 }
 
+// Proxy authentication
+pub mod auth;
+
+// Persisted event log replayed to reconnecting proxies
+pub mod event_log;
+
+// Proxies connected through the Connect stream
+pub mod session;
+
 // Message handler modules
 pub mod message;
 pub mod stream;
 
 use minecraft_bridge::{
-    EventSubscription, PlayerAccessRequest, PlayerAccessResponse, ProxyRegistration,
-    RegistrationResponse, ServerEvent,
+    EventSubscription, EventType, LinkCodeRedemption, LinkCodeResponse, PlayerAccessRequest,
+    PlayerAccessResponse, ProxyMessage, ProxyRegistration, RegistrationResponse, ServerEvent,
+    TwigMessage,
     minecraft_bridge_server::{MinecraftBridge, MinecraftBridgeServer},
 };
 
@@ -63,6 +78,14 @@ impl MinecraftBridge for MinecraftBridgeService {
         message::minecraft_bridge::check_player_access(&self.state, request).await
     }
 
+    /// Link a player's Minecraft account using a code generated on Discord
+    async fn redeem_link_code(
+        &self,
+        request: Request<LinkCodeRedemption>,
+    ) -> Result<Response<LinkCodeResponse>, Status> {
+        message::minecraft_bridge::redeem_link_code(&self.state, request).await
+    }
+
     /// Subscribe to server events (server-streaming)
     type SubscribeEventsStream =
         Pin<Box<dyn Stream<Item = Result<ServerEvent, Status>> + Send + 'static>>;
@@ -71,46 +94,40 @@ impl MinecraftBridge for MinecraftBridgeService {
         &self,
         request: Request<EventSubscription>,
     ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        auth::authorize_proxy(&request, &request.get_ref().proxy_id)?;
+
+        let key_hash = auth::key_hash(&request);
         let subscription = request.into_inner();
-        let proxy_id = subscription.proxy_id;
-        let event_types = subscription.event_types;
+        let filter = EventFilter {
+            proxy_id: subscription.proxy_id,
+            event_types: subscription.event_types,
+            accepts_commands: false,
+            key_hash,
+        };
 
         info!(
-            "[gRPC::SubscribeEvents] Client {} subscribing to events: {:?}",
-            proxy_id, event_types
+            "[gRPC::SubscribeEvents] Client {} subscribing to events: {:?} (last seen: {:?})",
+            filter.proxy_id, filter.event_types, subscription.last_seen_sequence
         );
 
-        // Subscribe to the broadcast channel
-        let mut rx = self.state.event_tx.subscribe();
-
         // Create a channel to convert broadcast to mpsc for streaming
-        let (tx, stream_rx) = tokio::sync::mpsc::channel(100);
-
-        // Spawn a task to forward events from broadcast to mpsc
-        tokio::spawn(async move {
-            while let Ok(event) = rx.recv().await {
-                // Filter by event type
-                let type_matches =
-                    event_types.is_empty() || event_types.contains(&event.event_type);
-
-                // Filter by target proxy ID (None or empty = broadcast to all)
-                let proxy_matches = event
-                    .target_proxy_id
-                    .as_ref()
-                    .map(|target| target.is_empty() || target == &proxy_id)
-                    .unwrap_or(true);
-
-                if type_matches && proxy_matches {
-                    if tx.send(Ok(event)).await.is_err() {
-                        break; // Client disconnected
-                    }
-                }
-            }
-        });
+        let (tx, stream_rx) = mpsc::channel::<Result<ServerEvent, Status>>(100);
+
+        forward_events(&self.state, filter, subscription.last_seen_sequence, tx).await?;
 
         let stream = ReceiverStream::new(stream_rx);
         Ok(Response::new(Box::pin(stream)))
     }
+
+    /// Open a session carrying heartbeats, player notifications and events (bidirectional streaming)
+    type ConnectStream = Pin<Box<dyn Stream<Item = Result<TwigMessage, Status>> + Send + 'static>>;
+
+    async fn connect(
+        &self,
+        request: Request<Streaming<ProxyMessage>>,
+    ) -> Result<Response<Self::ConnectStream>, Status> {
+        message::minecraft_bridge::connect(&self.state, request).await
+    }
 }
 
 /// Start the gRPC server
@@ -126,23 +143,313 @@ pub async fn start_grpc_server(
     };
 
     let service = MinecraftBridgeService::new(state.clone());
+    let interceptor = auth::ProxyAuthInterceptor::new(Arc::clone(&data.proxy_keys));
+
+    if !config::get_config().grpc_auth {
+        warn!("[gRPC] Proxy authentication is disabled, anyone can reach the gRPC service");
+    }
+
+    let mut builder = Server::builder();
+    if let Some(tls_config) = tls_config().await? {
+        builder = builder.tls_config(tls_config)?;
+    }
 
     info!("[gRPC] Starting gRPC server on {}", addr);
 
-    Server::builder()
-        .add_service(MinecraftBridgeServer::new(service))
+    builder
+        .add_service(MinecraftBridgeServer::with_interceptor(
+            service,
+            interceptor,
+        ))
         .serve(addr)
         .await?;
 
     Ok(())
 }
 
-/// Helper function to broadcast an event to all subscribers
+/// Builds the TLS configuration of the gRPC server from the config, if enabled
+///
+/// When a client CA is set, proxies must present a certificate signed by it.
+async fn tls_config() -> Result<Option<ServerTlsConfig>, Box<dyn std::error::Error>> {
+    let config = config::get_config();
+
+    let (cert_path, key_path) = match (&config.grpc_tls_cert, &config.grpc_tls_key) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) => {
+            if config.grpc_tls_client_ca.is_some() {
+                return Err(
+                    "`GRPC_TLS_CLIENT_CA` requires `GRPC_TLS_CERT` and `GRPC_TLS_KEY`".into(),
+                );
+            }
+
+            warn!("[gRPC] TLS is disabled, traffic to the proxies is not encrypted");
+            return Ok(None);
+        }
+        _ => return Err("`GRPC_TLS_CERT` and `GRPC_TLS_KEY` must be set together".into()),
+    };
+
+    let cert = tokio::fs::read(cert_path).await?;
+    let key = tokio::fs::read(key_path).await?;
+    let mut tls_config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    if let Some(client_ca_path) = &config.grpc_tls_client_ca {
+        let client_ca = tokio::fs::read(client_ca_path).await?;
+        tls_config = tls_config.client_ca_root(Certificate::from_pem(client_ca));
+
+        info!("[gRPC] Mutual TLS enabled, proxies must present a client certificate");
+    } else {
+        info!("[gRPC] TLS enabled");
+    }
+
+    Ok(Some(tls_config))
+}
+
+/// Forwards the broadcast events matching `filter` to a proxy's stream
+///
+/// Events missed since `last_seen_sequence` are replayed from the log first.
+/// The returned task ends when the client disconnects or its API key is revoked.
+pub async fn forward_events<T: From<ServerEvent> + Send + 'static>(
+    state: &GrpcServiceState,
+    filter: EventFilter,
+    last_seen_sequence: Option<u64>,
+    tx: mpsc::Sender<Result<T, Status>>,
+) -> Result<JoinHandle<()>, Status> {
+    let db = state.data.db.clone();
+    let read_latest_sequence = async || {
+        event_log::latest_sequence(&db).await.map_err(|e| {
+            warn!(
+                "[gRPC::SubscribeEvents] Failed to read the event log: {}",
+                e
+            );
+            Status::internal("Database error")
+        })
+    };
+
+    // Without a replay, the stream starts at the end of the log, read before subscribing so
+    // every event later received from the channel is newer and none is skipped as a duplicate
+    let start_sequence = match last_seen_sequence {
+        Some(_) => None,
+        None => Some(read_latest_sequence().await?),
+    };
+
+    // Subscribe to the broadcast channel before reading the log, so no event falls in between
+    let mut rx = state.event_tx.subscribe();
+    let proxy_keys = Arc::clone(&state.data.proxy_keys);
+    let mut revocations = proxy_keys.subscribe_revocations();
+
+    let latest_sequence = match start_sequence {
+        Some(sequence) => sequence,
+        None => read_latest_sequence().await?,
+    };
+
+    // Spawn a task to forward events from broadcast to mpsc
+    Ok(tokio::spawn(async move {
+        // Sequence of the last event handled for this proxy
+        let mut last_sequence = latest_sequence;
+
+        // Replay the events missed while the proxy was disconnected
+        if let Some(last_seen_sequence) = last_seen_sequence {
+            // A sequence ahead of the log means the log was reset, replay all of it
+            let from = if last_seen_sequence > latest_sequence {
+                0
+            } else {
+                last_seen_sequence
+            };
+
+            match replay(&db, &filter, from, &tx).await {
+                Some(sequence) => last_sequence = sequence.max(latest_sequence),
+                None => return, // Client disconnected
+            }
+        }
+
+        // A rotated key revokes the previous one too
+        let is_revoked = |filter: &EventFilter| {
+            config::get_config().grpc_auth
+                && !filter
+                    .key_hash
+                    .as_deref()
+                    .is_some_and(|key_hash| proxy_keys.is_current(&filter.proxy_id, key_hash))
+        };
+
+        loop {
+            let received = tokio::select! {
+                received = rx.recv() => received,
+                // Close idle streams as soon as the key is revoked, not on the next event
+                Ok(()) = revocations.changed() => {
+                    if is_revoked(&filter) {
+                        let _ = tx
+                            .send(Err(Status::unauthenticated("Proxy API key was revoked")))
+                            .await;
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            let event = match received {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(
+                        "[gRPC::SubscribeEvents] Client {} lagged behind by {} events, resynchronizing from the log",
+                        filter.proxy_id, skipped
+                    );
+
+                    match replay(&db, &filter, last_sequence, &tx).await {
+                        Some(sequence) => last_sequence = sequence,
+                        None => break, // Client disconnected
+                    }
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            // Close the stream once the proxy's API key is revoked
+            if is_revoked(&filter) {
+                let _ = tx
+                    .send(Err(Status::unauthenticated("Proxy API key was revoked")))
+                    .await;
+                break;
+            }
+
+            // Skip events already replayed from the log
+            if event.sequence != 0 {
+                if event.sequence <= last_sequence {
+                    continue;
+                }
+                last_sequence = event.sequence;
+            }
+
+            if filter.matches(&event) && tx.send(Ok(event.into())).await.is_err() {
+                break; // Client disconnected
+            }
+        }
+    }))
+}
+
+/// Events a subscribed proxy is interested in
+pub struct EventFilter {
+    pub proxy_id: String,
+    pub event_types: Vec<i32>,
+    /// Whether the stream is a `Connect` session, the only one able to acknowledge commands
+    pub accepts_commands: bool,
+    /// Hash of the API key the stream was opened with, `None` when authentication is disabled
+    pub key_hash: Option<String>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &ServerEvent) -> bool {
+        // Filter by event type
+        let type_matches =
+            self.event_types.is_empty() || self.event_types.contains(&event.event_type);
+
+        // Filter by target proxy IDs, falling back to the single target (None or empty = broadcast to all)
+        let proxy_matches = if event.target_proxy_ids.is_empty() {
+            event
+                .target_proxy_id
+                .as_ref()
+                .map(|target| target.is_empty() || target == &self.proxy_id)
+                .unwrap_or(true)
+        } else {
+            event.target_proxy_ids.contains(&self.proxy_id)
+        };
+
+        // Commands sent through `SubscribeEvents` would be carried out a second time
+        let command_matches =
+            self.accepts_commands || !stream::minecraft_bridge::command::is_command(event);
+
+        type_matches && proxy_matches && command_matches
+    }
+}
+
+/// Sends the logged events after `sequence` to a subscriber
+///
+/// When the log no longer holds every missed event (or can't be read), a
+/// `RESYNC` event asks the proxy to re-check all its online players instead.
+/// Commands nobody waits for anymore are skipped.
+/// Returns the sequence of the last replayed event, or `None` if the client disconnected.
+async fn replay<T: From<ServerEvent>>(
+    db: &sqlx::SqlitePool,
+    filter: &EventFilter,
+    sequence: u64,
+    tx: &mpsc::Sender<Result<T, Status>>,
+) -> Option<u64> {
+    let (events, complete) = match event_log::bounds(db).await {
+        Ok(Some((oldest, _))) => match event_log::since(db, sequence).await {
+            Ok(events) => (events, oldest <= sequence + 1),
+            Err(e) => {
+                warn!(
+                    "[gRPC::SubscribeEvents] Failed to read the event log: {}",
+                    e
+                );
+                (Vec::new(), false)
+            }
+        },
+        // Nothing is logged, missed events may have been purged already
+        Ok(None) => (Vec::new(), sequence == 0),
+        Err(e) => {
+            warn!(
+                "[gRPC::SubscribeEvents] Failed to read the event log: {}",
+                e
+            );
+            (Vec::new(), false)
+        }
+    };
+
+    if !complete {
+        warn!(
+            "[gRPC::SubscribeEvents] Missed events of client {} aren't all logged anymore, requesting a resync",
+            filter.proxy_id
+        );
+
+        let resync = ServerEvent {
+            event_type: EventType::Resync as i32,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64,
+            target_proxy_id: None,
+            target_proxy_ids: Vec::new(),
+            sequence: 0,
+            event_data: None,
+        };
+
+        tx.send(Ok(resync.into())).await.ok()?;
+    }
+
+    let mut last_sequence = sequence;
+    let replayed = events.len();
+    for event in events {
+        last_sequence = event.sequence;
+
+        if filter.matches(&event) && !stream::minecraft_bridge::command::is_expired_command(&event)
+        {
+            tx.send(Ok(event.into())).await.ok()?;
+        }
+    }
+
+    info!(
+        "[gRPC::SubscribeEvents] Replayed {} logged events to client {} (up to {})",
+        replayed, filter.proxy_id, last_sequence
+    );
+
+    Some(last_sequence)
+}
+
+/// Helper function to log an event and broadcast it to all subscribers
 pub async fn broadcast_event(
-    event_tx: &broadcast::Sender<ServerEvent>,
-    event: ServerEvent,
+    data: &crate::Data,
+    mut event: ServerEvent,
 ) -> Result<usize, Box<dyn std::error::Error>> {
-    match event_tx.send(event) {
+    // Hold the log lock until the event is sent, subscribers rely on the sequence order
+    let _guard = match event_log::append(&data.db, &mut event).await {
+        Ok(guard) => Some(guard),
+        Err(e) => {
+            warn!("[gRPC] Failed to log event, it won't be replayed: {}", e);
+            None
+        }
+    };
+
+    match data.grpc_event_tx.send(event) {
         Ok(receivers) => {
             info!("[gRPC] Event broadcasted to {} receivers", receivers);
             Ok(receivers)
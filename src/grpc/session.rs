@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
+
+use crate::grpc::minecraft_bridge::CommandAck;
+
+/// A player connected through a proxy, as reported over `Connect`
+#[derive(Clone)]
+pub struct OnlinePlayer {
+    pub player_name: String,
+    pub server_name: String,
+}
+
+/// A live `Connect` stream of a proxy
+struct ProxySession {
+    session_id: i64,
+    connected_at: i64,
+    last_heartbeat: Instant,
+    players: HashMap<String, OnlinePlayer>,
+}
+
+/// Snapshot of a connected proxy
+pub struct ConnectedProxy {
+    pub proxy_id: String,
+    /// Unix timestamp in milliseconds
+    pub connected_at: i64,
+    pub last_heartbeat: Duration,
+    pub players: Vec<OnlinePlayer>,
+}
+
+/// Registry of the proxies currently connected through `Connect`
+///
+/// A proxy has at most one session; reconnecting replaces the previous one,
+/// whose stream only closes its own session (matched by `session_id`).
+#[derive(Default)]
+pub struct ProxySessions {
+    sessions: RwLock<HashMap<String, ProxySession>>,
+    /// Commands awaiting a `CommandAck`, by command ID, with the proxy they were sent to
+    pending_acks: Mutex<HashMap<i64, (String, oneshot::Sender<CommandAck>)>>,
+}
+
+impl ProxySessions {
+    /// Opens a session for the proxy, replacing any previous one
+    pub fn open(&self, proxy_id: &str, session_id: i64) {
+        let session = ProxySession {
+            session_id,
+            connected_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64,
+            last_heartbeat: Instant::now(),
+            players: HashMap::new(),
+        };
+
+        if self
+            .sessions
+            .write()
+            .unwrap()
+            .insert(proxy_id.to_string(), session)
+            .is_some()
+        {
+            warn!(
+                "[ProxySessions::open] Proxy `{}` reconnected, replacing its previous session",
+                proxy_id
+            );
+        }
+
+        info!(
+            "[ProxySessions::open] Proxy `{}` connected (session {})",
+            proxy_id, session_id
+        );
+    }
+
+    /// Closes the proxy's session if it's still the given one, returns whether it was
+    pub fn close(&self, proxy_id: &str, session_id: i64) -> bool {
+        let mut sessions = self.sessions.write().unwrap();
+
+        if !sessions
+            .get(proxy_id)
+            .is_some_and(|session| session.session_id == session_id)
+        {
+            return false;
+        }
+
+        sessions.remove(proxy_id);
+
+        info!(
+            "[ProxySessions::close] Proxy `{}` disconnected (session {})",
+            proxy_id, session_id
+        );
+
+        true
+    }
+
+    /// Records a heartbeat, returns `false` if the session was replaced
+    pub fn heartbeat(&self, proxy_id: &str, session_id: i64) -> bool {
+        self.with_session(proxy_id, session_id, |session| {
+            session.last_heartbeat = Instant::now();
+        })
+    }
+
+    /// Records a player joining (or moving to) a server of the proxy
+    pub fn player_join(
+        &self,
+        proxy_id: &str,
+        session_id: i64,
+        player_uuid: String,
+        player: OnlinePlayer,
+    ) -> bool {
+        self.with_session(proxy_id, session_id, |session| {
+            session.players.insert(player_uuid, player);
+        })
+    }
+
+    /// Records a player leaving the proxy
+    pub fn player_leave(&self, proxy_id: &str, session_id: i64, player_uuid: &str) -> bool {
+        self.with_session(proxy_id, session_id, |session| {
+            session.players.remove(player_uuid);
+        })
+    }
+
+    /// Returns whether the proxy has a live session
+    pub fn is_connected(&self, proxy_id: &str) -> bool {
+        self.sessions.read().unwrap().contains_key(proxy_id)
+    }
+
+    /// Registers a command sent to the proxy, the receiver resolves once it's acknowledged
+    pub fn expect_ack(&self, proxy_id: &str, command_id: i64) -> oneshot::Receiver<CommandAck> {
+        let (tx, rx) = oneshot::channel();
+
+        self.pending_acks
+            .lock()
+            .unwrap()
+            .insert(command_id, (proxy_id.to_string(), tx));
+
+        rx
+    }
+
+    /// Stops waiting for the acknowledgement of a command
+    pub fn forget_ack(&self, command_id: i64) {
+        self.pending_acks.lock().unwrap().remove(&command_id);
+    }
+
+    /// Resolves a pending command with the proxy's acknowledgement
+    ///
+    /// Acknowledgements from another proxy than the command's are ignored.
+    pub fn acknowledge(&self, proxy_id: &str, ack: CommandAck) {
+        let mut pending_acks = self.pending_acks.lock().unwrap();
+
+        if !pending_acks
+            .get(&ack.command_id)
+            .is_some_and(|(target, _)| target == proxy_id)
+        {
+            debug!(
+                "[ProxySessions::acknowledge] Proxy `{}` acknowledged unknown command {}",
+                proxy_id, ack.command_id
+            );
+            return;
+        }
+
+        if let Some((_, tx)) = pending_acks.remove(&ack.command_id) {
+            let _ = tx.send(ack);
+        }
+    }
+
+    /// Returns a snapshot of every connected proxy
+    pub fn connected(&self) -> Vec<ConnectedProxy> {
+        self.sessions
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(proxy_id, session)| ConnectedProxy {
+                proxy_id: proxy_id.clone(),
+                connected_at: session.connected_at,
+                last_heartbeat: session.last_heartbeat.elapsed(),
+                players: session.players.values().cloned().collect(),
+            })
+            .collect()
+    }
+
+    fn with_session(
+        &self,
+        proxy_id: &str,
+        session_id: i64,
+        f: impl FnOnce(&mut ProxySession),
+    ) -> bool {
+        match self.sessions.write().unwrap().get_mut(proxy_id) {
+            Some(session) if session.session_id == session_id => {
+                f(session);
+                true
+            }
+            _ => false,
+        }
+    }
+}
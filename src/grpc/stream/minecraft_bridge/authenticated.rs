@@ -1,14 +1,20 @@
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::EventTarget;
 use crate::grpc;
 use crate::grpc::minecraft_bridge::{
     EventType, PlayerUpdateEvent, ServerEvent, server_event::EventData,
 };
+use crate::utils::ip;
+
+pub async fn broadcast_event(data: Arc<crate::Data>, minecraft_user_id: i64, target: EventTarget) {
+    let Some(target_proxy_ids) = target.resolve(&data).await else {
+        return;
+    };
 
-pub async fn broadcast_event(data: Arc<crate::Data>, minecraft_user_id: i64) {
     let record = sqlx::query!(
-        "SELECT player_name, player_ipv4 FROM minecraft_users WHERE id = $1",
+        "SELECT player_uuid, player_name, player_ip FROM minecraft_users WHERE id = $1",
         minecraft_user_id
     )
     .fetch_one(&data.db)
@@ -16,7 +22,7 @@ pub async fn broadcast_event(data: Arc<crate::Data>, minecraft_user_id: i64) {
     .unwrap();
 
     let _ = grpc::broadcast_event(
-        &data.grpc_event_tx,
+        &data,
         ServerEvent {
             event_type: EventType::PlayerUpdate as i32,
             timestamp: SystemTime::now()
@@ -24,9 +30,13 @@ pub async fn broadcast_event(data: Arc<crate::Data>, minecraft_user_id: i64) {
                 .unwrap()
                 .as_millis() as i64,
             target_proxy_id: None,
+            target_proxy_ids,
+            sequence: 0,
             event_data: Some(EventData::PlayerUpdate(PlayerUpdateEvent {
                 player_name: record.player_name,
-                player_ipv4: record.player_ipv4,
+                player_ipv4: ip::ipv4_or_empty(&record.player_ip),
+                player_ip: record.player_ip,
+                player_uuid: record.player_uuid.unwrap_or_default(),
             })),
         },
     )
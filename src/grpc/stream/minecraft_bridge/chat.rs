@@ -0,0 +1,39 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tracing::debug;
+
+use crate::grpc::minecraft_bridge::{
+    DiscordChatMessage, EventType, ServerEvent, server_event::EventData,
+};
+
+/// Sends a message written in a bound Discord channel to the players of the server
+///
+/// Chat isn't kept in the event log, proxies away when it was sent never receive it.
+pub async fn relay_to_minecraft(
+    data: &crate::Data,
+    proxy_id: String,
+    server_name: String,
+    author_name: String,
+    message: String,
+) {
+    // A sequence of 0 tells the forwarders the event wasn't logged
+    let sent = data.grpc_event_tx.send(ServerEvent {
+        event_type: EventType::ChatMessage as i32,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64,
+        target_proxy_id: None,
+        target_proxy_ids: vec![proxy_id],
+        sequence: 0,
+        event_data: Some(EventData::DiscordChat(DiscordChatMessage {
+            server_name,
+            author_name,
+            message,
+        })),
+    });
+
+    if let Err(e) = sent {
+        debug!("[chat::relay_to_minecraft] No proxy is listening: {}", e);
+    }
+}
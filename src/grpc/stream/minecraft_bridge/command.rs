@@ -0,0 +1,146 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::{info, warn};
+
+use crate::grpc;
+use crate::grpc::minecraft_bridge::{
+    BroadcastMessage, CommandAck, EventType, KickPlayer, ServerEvent, server_event::EventData,
+};
+use crate::utils::snowflake;
+
+/// How long a moderator waits for the proxy to acknowledge a command
+///
+/// Commands older than this aren't replayed to reconnecting proxies either.
+pub const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Asks the proxy to kick a player, returns its acknowledgement
+///
+/// Returns `None` when the proxy isn't connected or didn't acknowledge the command in time.
+pub async fn kick_player(
+    data: &crate::Data,
+    proxy_id: &str,
+    player_uuid: String,
+    player_name: String,
+    reason: String,
+) -> Option<CommandAck> {
+    let command_id = snowflake::generate_id();
+
+    info!(
+        "[command::kick_player] Kicking player `{}` from proxy `{}` (command {})",
+        player_name, proxy_id, command_id
+    );
+
+    let command = EventData::KickPlayer(KickPlayer {
+        command_id,
+        player_uuid,
+        player_name,
+        reason,
+    });
+
+    send(data, proxy_id, command_id, EventType::KickPlayer, command).await
+}
+
+/// Asks the proxy to show a message to the players of one of its servers, returns its acknowledgement
+///
+/// Returns `None` when the proxy isn't connected or didn't acknowledge the command in time.
+pub async fn broadcast_message(
+    data: &crate::Data,
+    proxy_id: &str,
+    server_name: String,
+    message: String,
+    sender: String,
+) -> Option<CommandAck> {
+    let command_id = snowflake::generate_id();
+
+    info!(
+        "[command::broadcast_message] Broadcasting to server `{}` of proxy `{}` (command {})",
+        server_name, proxy_id, command_id
+    );
+
+    let command = EventData::BroadcastMessage(BroadcastMessage {
+        command_id,
+        server_name,
+        message,
+        sender,
+    });
+
+    send(
+        data,
+        proxy_id,
+        command_id,
+        EventType::BroadcastMessage,
+        command,
+    )
+    .await
+}
+
+/// Returns whether the event is a command (or chat message), only delivered through `Connect` sessions
+pub fn is_command(event: &ServerEvent) -> bool {
+    matches!(
+        event.event_data,
+        Some(EventData::KickPlayer(_))
+            | Some(EventData::BroadcastMessage(_))
+            | Some(EventData::DiscordChat(_))
+    )
+}
+
+/// Returns whether the event is a command (or chat message) that's too old to be carried out anymore
+pub fn is_expired_command(event: &ServerEvent) -> bool {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    is_command(event) && event.timestamp < now_ms - COMMAND_TIMEOUT.as_millis() as i64
+}
+
+/// Sends a command to a single proxy and waits for its acknowledgement
+///
+/// Returns `None` right away when the proxy has no live `Connect` session to acknowledge it.
+async fn send(
+    data: &crate::Data,
+    proxy_id: &str,
+    command_id: i64,
+    event_type: EventType,
+    command: EventData,
+) -> Option<CommandAck> {
+    if !data.proxy_sessions.is_connected(proxy_id) {
+        warn!(
+            "[command::send] Proxy `{}` isn't connected, dropping command {}",
+            proxy_id, command_id
+        );
+        return None;
+    }
+
+    // Register before sending, the proxy may answer right away
+    let ack = data.proxy_sessions.expect_ack(proxy_id, command_id);
+
+    let _ = grpc::broadcast_event(
+        data,
+        ServerEvent {
+            event_type: event_type as i32,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as i64,
+            target_proxy_id: None,
+            target_proxy_ids: vec![proxy_id.to_string()],
+            sequence: 0,
+            event_data: Some(command),
+        },
+    )
+    .await;
+
+    match tokio::time::timeout(COMMAND_TIMEOUT, ack).await {
+        Ok(Ok(ack)) => Some(ack),
+        _ => {
+            warn!(
+                "[command::send] Proxy `{}` didn't acknowledge command {}",
+                proxy_id, command_id
+            );
+
+            data.proxy_sessions.forget_ack(command_id);
+            None
+        }
+    }
+}
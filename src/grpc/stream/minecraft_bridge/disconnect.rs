@@ -4,18 +4,46 @@
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use poise::serenity_prelude::GuildId;
+
+use super::EventTarget;
 use crate::grpc;
 use crate::grpc::minecraft_bridge::{
     EventType, PlayerUpdateEvent, ServerEvent, server_event::EventData,
 };
+use crate::utils::ip;
 
 pub async fn guild_member_removal(
     data: Arc<crate::Data>,
+    guild_id: GuildId,
+    player_uuid: Option<String>,
     player_name: String,
-    player_ipv4: String,
+    player_ip: String,
 ) {
+    player_update(
+        data,
+        EventTarget::Guild(guild_id),
+        player_uuid,
+        player_name,
+        player_ip,
+    )
+    .await;
+}
+
+/// Asks the targeted proxies to re-check a player
+pub async fn player_update(
+    data: Arc<crate::Data>,
+    target: EventTarget,
+    player_uuid: Option<String>,
+    player_name: String,
+    player_ip: String,
+) {
+    let Some(target_proxy_ids) = target.resolve(&data).await else {
+        return;
+    };
+
     let _ = grpc::broadcast_event(
-        &data.grpc_event_tx,
+        &data,
         ServerEvent {
             event_type: EventType::PlayerUpdate as i32,
             timestamp: SystemTime::now()
@@ -23,9 +51,13 @@ pub async fn guild_member_removal(
                 .unwrap()
                 .as_millis() as i64,
             target_proxy_id: None,
+            target_proxy_ids,
+            sequence: 0,
             event_data: Some(EventData::PlayerUpdate(PlayerUpdateEvent {
                 player_name,
-                player_ipv4,
+                player_ipv4: ip::ipv4_or_empty(&player_ip),
+                player_ip,
+                player_uuid: player_uuid.unwrap_or_default(),
             })),
         },
     )
@@ -1,2 +1,58 @@
 pub mod authenticated;
+pub mod chat;
+pub mod command;
 pub mod disconnect;
+
+use poise::serenity_prelude::GuildId;
+use tracing::{debug, warn};
+
+/// Proxies an event is sent to
+pub enum EventTarget {
+    /// Every connected proxy
+    All,
+    /// The proxies bound to a Discord guild
+    Guild(GuildId),
+    /// Specific proxies
+    Proxies(Vec<String>),
+}
+
+impl EventTarget {
+    /// Resolves the `target_proxy_ids` of the event
+    ///
+    /// Returns an empty list to broadcast to every proxy, or `None` when no
+    /// proxy is targeted and the event shouldn't be sent at all.
+    pub async fn resolve(self, data: &crate::Data) -> Option<Vec<String>> {
+        let proxy_ids = match self {
+            EventTarget::All => return Some(Vec::new()),
+            EventTarget::Guild(guild_id) => {
+                let guild_id = guild_id.get() as i64;
+
+                match sqlx::query!(
+                    "SELECT id FROM minecraft_proxies WHERE discord_guild_id = ?1",
+                    guild_id
+                )
+                .fetch_all(&data.db)
+                .await
+                {
+                    Ok(records) => records.into_iter().map(|record| record.id).collect(),
+                    Err(e) => {
+                        warn!(
+                            "[EventTarget::resolve] Failed to fetch proxies of guild {}: {}",
+                            guild_id, e
+                        );
+
+                        return None;
+                    }
+                }
+            }
+            EventTarget::Proxies(proxy_ids) => proxy_ids,
+        };
+
+        if proxy_ids.is_empty() {
+            debug!("[EventTarget::resolve] No proxy targeted, skipping event");
+            return None;
+        }
+
+        Some(proxy_ids)
+    }
+}
@@ -4,8 +4,9 @@ use reqwest::Url;
 use serde::Deserialize;
 use tracing::warn;
 
-use crate::grpc::stream::minecraft_bridge;
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
 use crate::utils::config;
+use crate::utils::linking::{self, LinkError};
 use crate::utils::snowflake::is_snowflake_recent;
 
 #[derive(Deserialize)]
@@ -39,18 +40,31 @@ pub async fn oauth_callback(
     };
 
     // Check if is a valid state in db
-    let Some((minecraft_user_id, minecraft_registrations_id)) = sqlx::query!(
-        "SELECT minecraft_users.id as user_id, minecraft_registrations.id as regs_id FROM minecraft_registrations JOIN minecraft_users ON minecraft_registrations.minecraft_user_id = minecraft_users.id WHERE state_token = $1 AND minecraft_users.discord_user_id IS NULL",
+    let Some(registration) = sqlx::query!(
+        "SELECT minecraft_users.id as user_id, minecraft_registrations.id as regs_id,
+            minecraft_users.player_ip, minecraft_registrations.player_ip as regs_player_ip
+        FROM minecraft_registrations
+        JOIN minecraft_users ON minecraft_registrations.minecraft_user_id = minecraft_users.id
+        WHERE state_token = $1",
         state
-    ).fetch_one(&app_state.data.db).await.map_err(|_| {
-        (StatusCode::BAD_REQUEST, "Invalid state token".to_string())
-    }).map(|record| (record.user_id, record.regs_id)).ok() else {
+    )
+    .fetch_optional(&app_state.data.db)
+    .await
+    .ok()
+    .flatten() else {
         return Err((StatusCode::BAD_REQUEST, "Invalid state token".to_string()));
     };
 
-    // Check if registration is less than 5 minutes old
-    const FIVE_MINUTES_MS: u64 = 5 * 60 * 1000;
-    if !is_snowflake_recent(minecraft_registrations_id, FIVE_MINUTES_MS) {
+    let minecraft_user_id = registration.user_id;
+    let minecraft_registrations_id = registration.regs_id;
+    let player_ip = registration
+        .regs_player_ip
+        .clone()
+        .unwrap_or_else(|| registration.player_ip.clone());
+
+    // Check if registration is still within its TTL
+    let registration_ttl_ms = config::get_config().registration_ttl_secs * 1000;
+    if !is_snowflake_recent(minecraft_registrations_id, registration_ttl_ms) {
         return Err((
             StatusCode::BAD_REQUEST,
             "Registration token expired".to_string(),
@@ -126,36 +140,72 @@ pub async fn oauth_callback(
             )
         })?;
 
-    if let Some(_existing) = sqlx::query!(
-        "SELECT id FROM minecraft_users WHERE 
-        player_name = (SELECT player_name FROM minecraft_users WHERE id = (SELECT minecraft_user_id FROM minecraft_registrations WHERE state_token = $1)) AND discord_user_id != $2",
-        state,
-        user.id
-    ).fetch_optional(&app_state.data.db).await.unwrap() {
-        warn!("[Discord OAuth] Discord user {} is already linked to another Minecraft account.", user.id);
-
-        return Err((StatusCode::BAD_REQUEST, "Este usuário do Discord já está vinculado a outra conta Minecraft.".to_string()));
-    }
+    let Ok(discord_user_id) = user.id.parse::<i64>() else {
+        return Err((
+            StatusCode::BAD_GATEWAY,
+            "Invalid Discord user ID".to_string(),
+        ));
+    };
 
-    // Insert Discord user if not exists
-    let _ = sqlx::query!(
-        "INSERT OR IGNORE INTO discord_users (id) VALUES ($1)",
-        user.id
+    // Link Discord user to Minecraft user from the IP the registration came from
+    match linking::link_minecraft_user(
+        &app_state.data.db,
+        minecraft_user_id,
+        discord_user_id,
+        &player_ip,
     )
-    .execute(&app_state.data.db)
     .await
-    .unwrap();
+    {
+        Ok(()) => {}
+        Err(LinkError::AccountLinkedElsewhere) => {
+            warn!(
+                "[Discord OAuth] Minecraft user {} is already linked to another Discord user.",
+                minecraft_user_id
+            );
+
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Esta conta Minecraft já está vinculada a outro usuário do Discord.".to_string(),
+            ));
+        }
+        Err(LinkError::NameLinkedElsewhere) => {
+            warn!(
+                "[Discord OAuth] Discord user {} is already linked to another Minecraft account.",
+                user.id
+            );
+
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Este usuário do Discord já está vinculado a outra conta Minecraft.".to_string(),
+            ));
+        }
+        Err(LinkError::Database(e)) => {
+            warn!(
+                "[Discord OAuth] Failed to link Minecraft user {}: {}",
+                minecraft_user_id, e
+            );
+
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Database error".to_string(),
+            ));
+        }
+    }
 
-    // Link Discord user to Minecraft user
+    // Registrations are single-use
     let _ = sqlx::query!(
-        "UPDATE minecraft_users SET discord_user_id = $1 WHERE id = (SELECT minecraft_user_id FROM minecraft_registrations WHERE state_token = $2)",
-        user.id,
-        state
+        "DELETE FROM minecraft_registrations WHERE id = $1",
+        minecraft_registrations_id
     )
     .execute(&app_state.data.db)
-    .await.unwrap();
+    .await;
 
-    minecraft_bridge::authenticated::broadcast_event(app_state.data, minecraft_user_id).await;
+    minecraft_bridge::authenticated::broadcast_event(
+        app_state.data,
+        minecraft_user_id,
+        EventTarget::All,
+    )
+    .await;
 
     Ok("Sucesso!".to_string())
 }
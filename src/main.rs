@@ -7,6 +7,7 @@ mod commands;
 mod events;
 mod grpc;
 mod http;
+mod tasks;
 mod utils;
 
 // Types used by all command functions
@@ -20,6 +21,14 @@ pub struct Data {
     pub db: SqlitePool,
     /// gRPC event broadcaster (shared across all instances)
     pub grpc_event_tx: Arc<tokio::sync::broadcast::Sender<grpc::minecraft_bridge::ServerEvent>>,
+    /// Guild member cache fed by gateway events
+    pub member_cache: Arc<utils::member_cache::MemberCache>,
+    /// API keys of the Minecraft proxies allowed to use the gRPC service
+    pub proxy_keys: Arc<utils::proxy_keys::ProxyKeyStore>,
+    /// Proxies currently connected through the gRPC `Connect` stream
+    pub proxy_sessions: Arc<grpc::session::ProxySessions>,
+    /// Re-checks scheduled for when members' Discord timeouts run out
+    pub timeout_timers: Arc<utils::timeout_timers::TimeoutTimers>,
 }
 
 /// Custom error handler for the bot framework
@@ -122,10 +131,33 @@ pub async fn start() {
                     tokio::sync::broadcast::channel::<grpc::minecraft_bridge::ServerEvent>(100);
                 let event_tx = Arc::new(event_tx);
 
+                // Create the guild member cache
+                let member_cache = Arc::new(utils::member_cache::MemberCache::new(
+                    std::time::Duration::from_secs(
+                        utils::config::get_config().member_cache_ttl_secs,
+                    ),
+                ));
+
+                // Load the proxy API keys checked by the gRPC interceptor
+                let proxy_keys = Arc::new(
+                    utils::proxy_keys::ProxyKeyStore::load(&pool)
+                        .await
+                        .expect("Failed to load proxy API keys"),
+                );
+
+                // Player sessions left open by a previous run can't be resumed
+                utils::player_sessions::end_all(&pool, None, utils::player_sessions::now_ms())
+                    .await
+                    .expect("Failed to end stale player sessions");
+
                 // Create the Data structure
                 let data = Arc::new(Data {
                     db: pool,
                     grpc_event_tx: Arc::clone(&event_tx),
+                    member_cache,
+                    proxy_keys,
+                    proxy_sessions: Arc::new(grpc::session::ProxySessions::default()),
+                    timeout_timers: Arc::new(utils::timeout_timers::TimeoutTimers::default()),
                 });
 
                 // Clone context for gRPC server
@@ -159,6 +191,12 @@ pub async fn start() {
                     info!("[HTTP] Missing HTTP port configuration, skipping HTTP server startup");
                 }
 
+                // Spawn the janitor purging stale registrations and users
+                tokio::spawn(tasks::janitor::run(Arc::clone(&data)));
+
+                // Spawn the scheduler revoking expired access grants
+                tokio::spawn(tasks::grants::run(Arc::clone(&data)));
+
                 Ok(Arc::try_unwrap(data).unwrap_or_else(|arc| (*arc).clone()))
             })
         })
@@ -166,9 +204,14 @@ pub async fn start() {
         .build();
 
     let token = &utils::config::get_config().discord_token;
-    let intents =
+    let mut intents =
         serenity::GatewayIntents::non_privileged() | serenity::GatewayIntents::GUILD_MEMBERS;
 
+    // Only request the privileged Message Content intent when bound channels relay to Minecraft
+    if utils::config::get_config().chat_relay {
+        intents |= serenity::GatewayIntents::MESSAGE_CONTENT;
+    }
+
     let client = serenity::ClientBuilder::new(token, intents)
         .framework(framework)
         .await;
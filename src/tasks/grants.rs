@@ -0,0 +1,76 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::{info, warn};
+
+use crate::Data;
+use crate::grpc::stream::minecraft_bridge::{self, EventTarget};
+
+/// How often the scheduler looks for expired access grants
+const GRANTS_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Periodically revokes expired access grants and asks the proxies to re-check the players
+pub async fn run(data: Arc<Data>) {
+    info!("[grants::run] Starting access grant scheduler");
+
+    let mut interval = tokio::time::interval(GRANTS_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = revoke_expired(&data).await {
+            warn!("[grants::run] Failed to revoke expired grants: {}", e);
+        }
+    }
+}
+
+/// Deletes the expired grants and broadcasts a player update for every linked account
+async fn revoke_expired(data: &Arc<Data>) -> Result<(), sqlx::Error> {
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+
+    let expired = sqlx::query!(
+        "DELETE FROM minecraft_access_grants WHERE expires_at <= ?1
+        RETURNING server_id, discord_user_id",
+        now_ms
+    )
+    .fetch_all(&data.db)
+    .await?;
+
+    for grant in expired {
+        info!(
+            "[grants::revoke_expired] Access grant of user {} on server {} expired",
+            grant.discord_user_id, grant.server_id
+        );
+
+        // Only the proxy of the server has to re-check the player
+        let Some(server) = sqlx::query!(
+            "SELECT proxy_id FROM minecraft_servers WHERE id = ?1",
+            grant.server_id
+        )
+        .fetch_optional(&data.db)
+        .await?
+        else {
+            continue;
+        };
+
+        let records = sqlx::query!(
+            "SELECT id FROM minecraft_users WHERE discord_user_id = ?1",
+            grant.discord_user_id
+        )
+        .fetch_all(&data.db)
+        .await?;
+
+        for record in records {
+            minecraft_bridge::authenticated::broadcast_event(
+                Arc::clone(data),
+                record.id,
+                EventTarget::Proxies(vec![server.proxy_id.clone()]),
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::{debug, info, warn};
+
+use crate::Data;
+use crate::grpc::event_log;
+use crate::utils::{config, linking, snowflake};
+
+/// How often the janitor purges stale rows
+const JANITOR_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Periodically purges expired registrations, stale access records and never-linked Minecraft users
+pub async fn run(data: Arc<Data>) {
+    info!("[janitor::run] Starting janitor task");
+
+    let mut interval = tokio::time::interval(JANITOR_INTERVAL);
+    loop {
+        interval.tick().await;
+
+        if let Err(e) = purge(&data).await {
+            warn!("[janitor::run] Failed to purge stale rows: {}", e);
+        }
+    }
+}
+
+/// Deletes expired registrations, stale last allowed accesses, expired claim codes, old logged events and never-linked users older than the retention window
+async fn purge(data: &Data) -> Result<(), sqlx::Error> {
+    let config = config::get_config();
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+
+    // Registrations created before this ID are expired
+    let registration_cutoff = snowflake::timestamp_to_snowflake(
+        now_ms.saturating_sub(config.registration_ttl_secs * 1000),
+    );
+    let registrations = sqlx::query!(
+        "DELETE FROM minecraft_registrations WHERE id < ?1",
+        registration_cutoff
+    )
+    .execute(&data.db)
+    .await?;
+
+    // Users still registering are kept, as deleting them would cascade to the registration,
+    // and so are users with recorded playtime (e.g. unlinked accounts)
+    let retention_ms = config.unlinked_user_retention_days * 24 * 60 * 60 * 1000;
+    let user_cutoff = snowflake::timestamp_to_snowflake(now_ms.saturating_sub(retention_ms));
+    let users = sqlx::query!(
+        "DELETE FROM minecraft_users
+        WHERE discord_user_id IS NULL AND id < ?1
+            AND id NOT IN (SELECT minecraft_user_id FROM minecraft_registrations)
+            AND id NOT IN (SELECT minecraft_user_id FROM player_sessions)",
+        user_cutoff
+    )
+    .execute(&data.db)
+    .await?;
+
+    // Last allowed accesses older than the grace period can't be used anymore
+    let allowed_cutoff = now_ms.saturating_sub(config.fail_open_grace_secs * 1000) as i64;
+    let allowed = sqlx::query!(
+        "DELETE FROM minecraft_last_allowed WHERE allowed_at < ?1",
+        allowed_cutoff
+    )
+    .execute(&data.db)
+    .await?;
+
+    // Expired claim codes can't be redeemed anymore
+    let claim_cutoff = now_ms.saturating_sub(linking::CLAIM_CODE_EXPIRY_MS) as i64;
+    let claim_codes = sqlx::query!(
+        "UPDATE minecraft_proxies SET claim_code = NULL, claim_code_issued_at = NULL
+        WHERE claim_code IS NOT NULL AND claim_code_issued_at < ?1",
+        claim_cutoff
+    )
+    .execute(&data.db)
+    .await?;
+
+    // Proxies disconnected for longer than the retention get a resync instead of a replay
+    let event_cutoff = now_ms.saturating_sub(config.event_retention_hours * 60 * 60 * 1000) as i64;
+    let events = event_log::purge_before(&data.db, event_cutoff).await?;
+
+    if registrations.rows_affected() > 0
+        || users.rows_affected() > 0
+        || allowed.rows_affected() > 0
+        || claim_codes.rows_affected() > 0
+        || events > 0
+    {
+        info!(
+            "[janitor::purge] Purged {} expired registrations, {} never-linked Minecraft users, {} stale last allowed accesses, {} expired claim codes and {} old events",
+            registrations.rows_affected(),
+            users.rows_affected(),
+            allowed.rows_affected(),
+            claim_codes.rows_affected(),
+            events
+        );
+    } else {
+        debug!("[janitor::purge] Nothing to purge");
+    }
+
+    Ok(())
+}
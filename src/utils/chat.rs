@@ -0,0 +1,138 @@
+use poise::serenity_prelude::{self as serenity, CreateAllowedMentions, ExecuteWebhook};
+use sqlx::SqlitePool;
+use tracing::{debug, warn};
+
+/// Longest chat message relayed to Minecraft, longer ones are cut
+const MAX_MINECRAFT_LENGTH: usize = 256;
+
+/// Words Discord rejects in webhook usernames
+const FORBIDDEN_USERNAME_WORDS: [&str; 2] = ["discord", "clyde"];
+
+/// Escapes Discord markdown and breaks mentions in a player's message
+///
+/// Webhook posts also disallow every mention, this keeps `@everyone` and
+/// `<@id>` from even rendering as one.
+pub fn sanitize_for_discord(message: &str) -> String {
+    let mut sanitized = String::with_capacity(message.len());
+
+    for c in message.chars() {
+        match c {
+            '\\' | '*' | '_' | '~' | '`' | '|' | '>' | '#' | '-' | '[' | ']' => {
+                sanitized.push('\\');
+                sanitized.push(c);
+            }
+            // Zero-width space after `@` and `<` so mentions stay plain text
+            '@' | '<' => {
+                sanitized.push(c);
+                sanitized.push('\u{200B}');
+            }
+            _ => sanitized.push(c),
+        }
+    }
+
+    sanitized
+}
+
+/// Turns a Discord message into a single line of plain text for Minecraft
+///
+/// Expects mentions already resolved to names (see `Message::content_safe`),
+/// strips the markdown markers and cuts the message to `MAX_MINECRAFT_LENGTH`.
+pub fn sanitize_for_minecraft(message: &str) -> String {
+    let plain: String = message
+        .chars()
+        .filter(|c| !matches!(c, '*' | '_' | '~' | '`' | '|'))
+        // Minecraft formatting codes
+        .filter(|c| *c != '§')
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+
+    let plain = plain.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    match plain.char_indices().nth(MAX_MINECRAFT_LENGTH) {
+        Some((index, _)) => format!("{}…", &plain[..index]),
+        None => plain,
+    }
+}
+
+/// Breaks the words Discord rejects in webhook usernames, e.g. in `discordfan`
+///
+/// A zero-width space is inserted after their first letter, so the name still reads the same.
+pub fn sanitize_webhook_username(name: &str) -> String {
+    let mut sanitized = name.to_string();
+
+    for word in FORBIDDEN_USERNAME_WORDS {
+        // ASCII lowercasing keeps the byte offsets
+        while let Some(index) = sanitized.to_ascii_lowercase().find(word) {
+            sanitized.insert(index + 1, '\u{200B}');
+        }
+    }
+
+    sanitized
+}
+
+/// Posts a player's chat message in the Discord channel bound to the server
+///
+/// The webhook shows the player's name and skin head as the author. Servers
+/// without a bound channel are ignored.
+pub async fn relay_to_discord(
+    http: &serenity::Http,
+    db: &SqlitePool,
+    proxy_id: &str,
+    server_name: &str,
+    player_uuid: &str,
+    player_name: &str,
+    message: &str,
+) -> Result<(), crate::Error> {
+    let Some(channel) = sqlx::query!(
+        "SELECT minecraft_chat_channels.webhook_id, minecraft_chat_channels.webhook_token
+        FROM minecraft_chat_channels
+        JOIN minecraft_servers ON minecraft_chat_channels.server_id = minecraft_servers.id
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_servers.proxy_id = ?1 AND
+            minecraft_servers.server_name = ?2 AND
+            minecraft_proxies.discord_guild_id = minecraft_chat_channels.discord_guild_id",
+        proxy_id,
+        server_name
+    )
+    .fetch_optional(db)
+    .await?
+    else {
+        debug!(
+            "[chat::relay_to_discord] Server `{}` of proxy `{}` has no chat channel",
+            server_name, proxy_id
+        );
+
+        return Ok(());
+    };
+
+    let message = sanitize_for_discord(message);
+    if message.trim().is_empty() {
+        return Ok(());
+    }
+
+    let builder = ExecuteWebhook::new()
+        .content(message)
+        .username(sanitize_webhook_username(player_name))
+        .avatar_url(format!("https://mc-heads.net/avatar/{}", player_uuid))
+        .allowed_mentions(CreateAllowedMentions::new());
+
+    if let Err(e) = http
+        .execute_webhook(
+            serenity::WebhookId::new(channel.webhook_id as u64),
+            None,
+            &channel.webhook_token,
+            false,
+            Vec::new(),
+            &builder,
+        )
+        .await
+    {
+        warn!(
+            "[chat::relay_to_discord] Failed to relay a message of `{}` from server `{}`: {}",
+            player_name, server_name, e
+        );
+    }
+
+    Ok(())
+}
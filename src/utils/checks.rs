@@ -1,10 +1,11 @@
+use poise::serenity_prelude::{Member, Permissions};
 use tracing::debug;
 
 use crate::{Context, Error};
 
 /// Check if the user is the bot owner
 pub async fn is_owner(ctx: Context<'_>) -> Result<bool, Error> {
-    let owner = ctx.framework().options().owners.contains(&ctx.author().id);
+    let owner = is_bot_owner(ctx);
 
     if !owner {
         debug!("[is_owner] Attempted to use owner-only command, but user is not owner");
@@ -14,3 +15,84 @@ pub async fn is_owner(ctx: Context<'_>) -> Result<bool, Error> {
 
     Ok(owner)
 }
+
+/// Check if the user is a bot owner or has the Manage Server permission in the guild
+pub async fn is_guild_manager(ctx: Context<'_>) -> Result<bool, Error> {
+    let manager = is_bot_owner(ctx) || has_manage_guild(ctx).await;
+
+    if !manager {
+        debug!("[is_guild_manager] Attempted to use manager-only command without Manage Server");
+        ctx.say("❌ This command requires the Manage Server permission.")
+            .await?;
+    }
+
+    Ok(manager)
+}
+
+/// Check if the user can manage Twig in the guild
+///
+/// Bot owners, members with Manage Server and members holding the guild's
+/// Twig admin role (`/minecraft guild adminrole`) are allowed.
+pub async fn is_guild_admin(ctx: Context<'_>) -> Result<bool, Error> {
+    let admin = is_bot_owner(ctx) || has_manage_guild(ctx).await || has_admin_role(ctx).await?;
+
+    if !admin {
+        debug!("[is_guild_admin] Attempted to use admin-only command, but user is not admin");
+        ctx.say("❌ This command can only be used by the guild administrators.")
+            .await?;
+    }
+
+    Ok(admin)
+}
+
+/// Returns whether the user is one of the global bot owners
+fn is_bot_owner(ctx: Context<'_>) -> bool {
+    ctx.framework().options().owners.contains(&ctx.author().id)
+}
+
+/// Returns whether the user has Manage Server in the current guild
+async fn has_manage_guild(ctx: Context<'_>) -> bool {
+    let Some(member) = ctx.author_member().await else {
+        return false;
+    };
+
+    member_permissions(ctx, &member).is_some_and(|permissions| permissions.manage_guild())
+}
+
+/// Returns the member's guild permissions, as resolved by the interaction when available
+#[allow(deprecated)]
+fn member_permissions(ctx: Context<'_>, member: &Member) -> Option<Permissions> {
+    if let Some(permissions) = member.permissions {
+        return Some(permissions);
+    }
+
+    // Manage Server is a guild-wide permission, channel overwrites don't matter
+    ctx.guild().map(|guild| guild.member_permissions(member))
+}
+
+/// Returns whether the user holds the Twig admin role of the current guild
+async fn has_admin_role(ctx: Context<'_>) -> Result<bool, Error> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(false);
+    };
+
+    let guild_id_i64 = u64::from(guild_id) as i64;
+    let Some(admin_role_id) = sqlx::query!(
+        "SELECT admin_role_id FROM discord_guilds WHERE id = ?1",
+        guild_id_i64
+    )
+    .fetch_optional(&ctx.data().db)
+    .await?
+    .and_then(|record| record.admin_role_id) else {
+        return Ok(false);
+    };
+
+    let Some(member) = ctx.author_member().await else {
+        return Ok(false);
+    };
+
+    Ok(member
+        .roles
+        .iter()
+        .any(|role| role.get() as i64 == admin_role_id))
+}
@@ -2,7 +2,10 @@ use std::env;
 use std::sync::OnceLock;
 
 use poise::serenity_prelude::UserId;
-use tracing::{debug, info};
+use reqwest::Url;
+use tracing::{debug, info, warn};
+
+use crate::utils::minecraft::RegistrationMode;
 
 /// Application configuration loaded from environment variables
 #[derive(Debug)]
@@ -36,10 +39,38 @@ pub struct Config {
 
     // gRPC
     pub grpc_port: Option<u16>,
+    pub grpc_auth: bool,
+    pub grpc_tls_cert: Option<String>,
+    pub grpc_tls_key: Option<String>,
+    pub grpc_tls_client_ca: Option<String>,
 
     // HTTP Server
     pub http_port: Option<u16>,
     pub app_url: Option<String>,
+
+    // Registration
+    pub registration_mode: RegistrationMode,
+    pub registration_ttl_secs: u64,
+
+    // Player messages
+    pub default_locale: String,
+
+    // Discord outages
+    pub fail_open_grace_secs: u64,
+
+    // Janitor
+    pub unlinked_user_retention_days: u64,
+    pub event_retention_hours: u64,
+
+    // Caches
+    pub member_cache_ttl_secs: u64,
+
+    // Chat relay
+    pub chat_relay: bool,
+
+    // Player IP matching
+    pub ipv4_match_prefix: u8,
+    pub ipv6_match_prefix: u8,
 }
 
 /// Returns whether the application is running in debug mode
@@ -52,6 +83,12 @@ impl Config {
     fn from_env() -> Self {
         info!("[from_env] Loading configuration from environment variables");
 
+        let oauth_configured = is_oauth_configured(
+            env::var("APP_URL").ok().as_deref(),
+            env::var("DISCORD_OAUTH_CLIENT_ID").ok().as_deref(),
+            env::var("DISCORD_OAUTH_CLIENT_SECRET").ok().as_deref(),
+        );
+
         let config = Self {
             // Runtime info
             start_time: std::time::Instant::now(),
@@ -94,16 +131,119 @@ impl Config {
 
             // gRPC
             grpc_port: env::var("GRPC_PORT").ok().and_then(|p| p.parse().ok()),
+            // Proxy API keys are required unless explicitly disabled
+            grpc_auth: env::var("GRPC_AUTH")
+                .ok()
+                .and_then(|a| a.parse().ok())
+                .unwrap_or(true),
+            // PEM file paths, TLS is enabled when both the certificate and key are set
+            grpc_tls_cert: env::var("GRPC_TLS_CERT").ok(),
+            grpc_tls_key: env::var("GRPC_TLS_KEY").ok(),
+            grpc_tls_client_ca: env::var("GRPC_TLS_CLIENT_CA").ok(),
 
             // HTTP Server
             http_port: env::var("HTTP_PORT").ok().and_then(|p| p.parse().ok()),
             app_url: env::var("APP_URL").ok(),
+
+            // Registration
+            // Defaults to OAuth only when everything it needs is configured
+            registration_mode: match env::var("REGISTRATION_MODE").ok().as_deref() {
+                Some("oauth") if oauth_configured => RegistrationMode::OAuth,
+                Some("oauth") => {
+                    warn!(
+                        "[from_env] `REGISTRATION_MODE` is `oauth` but OAuth isn't configured, using `code`"
+                    );
+                    RegistrationMode::Code
+                }
+                Some("code") => RegistrationMode::Code,
+                other => {
+                    if let Some(value) = other {
+                        warn!(
+                            "[from_env] Invalid `REGISTRATION_MODE` `{}` (expected `oauth` or `code`), using the default",
+                            value
+                        );
+                    }
+
+                    // OAuth when it's configured, in-game codes otherwise
+                    if oauth_configured {
+                        RegistrationMode::OAuth
+                    } else {
+                        RegistrationMode::Code
+                    }
+                }
+            },
+            registration_ttl_secs: env::var("REGISTRATION_TTL")
+                .ok()
+                .and_then(|t| t.parse().ok())
+                .unwrap_or(300),
+
+            // Player messages
+            default_locale: env::var("DEFAULT_LOCALE").unwrap_or("en".into()),
+
+            // Discord outages
+            fail_open_grace_secs: env::var("FAIL_OPEN_GRACE")
+                .ok()
+                .and_then(|g| g.parse().ok())
+                .unwrap_or(24 * 60 * 60),
+
+            // Janitor
+            unlinked_user_retention_days: env::var("UNLINKED_USER_RETENTION_DAYS")
+                .ok()
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(30),
+            event_retention_hours: env::var("EVENT_RETENTION_HOURS")
+                .ok()
+                .and_then(|h| h.parse().ok())
+                .unwrap_or(24),
+
+            // Caches
+            member_cache_ttl_secs: env::var("MEMBER_CACHE_TTL")
+                .ok()
+                .and_then(|t| t.parse().ok())
+                .unwrap_or(600),
+
+            // Chat relay
+            // Relaying Discord messages needs the privileged Message Content intent
+            chat_relay: env::var("CHAT_RELAY")
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(false),
+
+            // Player IP matching
+            ipv4_match_prefix: env::var("IPV4_MATCH_PREFIX")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(32),
+            ipv6_match_prefix: env::var("IPV6_MATCH_PREFIX")
+                .ok()
+                .and_then(|p| p.parse().ok())
+                .unwrap_or(128),
         };
 
         debug!("[from_env] Loaded configuration: {:?}", config);
 
         config
     }
+
+    /// Returns whether players can link their Discord account through OAuth
+    pub fn is_oauth_configured(&self) -> bool {
+        is_oauth_configured(
+            self.app_url.as_deref(),
+            self.discord_oauth_client_id.as_deref(),
+            self.discord_oauth_client_secret.as_deref(),
+        )
+    }
+}
+
+/// Returns whether OAuth has a valid public URL and client credentials
+fn is_oauth_configured(
+    app_url: Option<&str>,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+) -> bool {
+    app_url.is_some_and(|url| Url::parse(url).is_ok())
+        && client_id.is_some()
+        && client_secret.is_some()
 }
 
 // A global, thread-safe, one-time initialized config
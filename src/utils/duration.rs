@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+/// Parses a human duration such as `90m`, `12h` or `2d12h`
+///
+/// Units are `s`, `m`, `h`, `d` and `w`. Returns `None` for empty, zero or invalid durations.
+pub fn parse(input: &str) -> Option<Duration> {
+    let mut total_secs: u64 = 0;
+    let mut number = String::new();
+
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit_secs = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+
+        let value: u64 = number.parse().ok()?;
+        total_secs = total_secs.checked_add(value.checked_mul(unit_secs)?)?;
+        number.clear();
+    }
+
+    // Trailing numbers without a unit are ambiguous
+    if !number.is_empty() || total_secs == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(total_secs))
+}
+
+/// Formats a duration as days, hours and minutes, e.g. `2d 3h 15m`
+pub fn format(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h {}m", days, hours, minutes),
+    }
+}
@@ -0,0 +1,47 @@
+use std::net::IpAddr;
+
+use crate::utils::config;
+
+/// Parses a player IP address, accepting both IPv4 and IPv6
+///
+/// IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`) are converted back to plain
+/// IPv4 so the same player is always stored under the same representation.
+///
+/// # Returns
+/// The canonical address, or `None` if the string is not a valid IP address
+pub fn parse(ip: &str) -> Option<IpAddr> {
+    ip.trim().parse::<IpAddr>().ok().map(|ip| ip.to_canonical())
+}
+
+/// Returns whether two addresses belong to the same network
+///
+/// The prefix lengths come from `IPV4_MATCH_PREFIX` and `IPV6_MATCH_PREFIX`, so
+/// an IPv6 privacy address rotating inside the same /64 can still be treated
+/// as the same player. Addresses of different families never match.
+pub fn same_network(a: IpAddr, b: IpAddr) -> bool {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            let prefix = config::get_config().ipv4_match_prefix.min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+
+            u32::from(a) & mask == u32::from(b) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => {
+            let prefix = config::get_config().ipv6_match_prefix.min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+
+            u128::from(a) & mask == u128::from(b) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Returns the address as a string if it is an IPv4 address, or an empty string otherwise
+///
+/// Used to fill the legacy `player_ipv4` proto fields for older proxies.
+pub fn ipv4_or_empty(ip: &str) -> String {
+    match parse(ip) {
+        Some(IpAddr::V4(ip)) => ip.to_string(),
+        _ => String::new(),
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sqlx::SqlitePool;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::utils::minecraft::TrustedIpStatus;
+use crate::utils::trusted_ips;
+
+/// Characters used in human-typable codes (no 0/O or 1/I/L look-alikes)
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// Length of human-typable codes
+const CODE_LENGTH: usize = 6;
+
+/// How long a link code stays valid
+pub const CODE_EXPIRY_MS: u64 = 5 * 60 * 1000;
+
+/// How long a proxy claim code stays valid
+pub const CLAIM_CODE_EXPIRY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Reasons a Minecraft account cannot be linked to a Discord user
+#[derive(Debug)]
+pub enum LinkError {
+    /// The Minecraft account is already linked to another Discord user
+    AccountLinkedElsewhere,
+    /// The player name is already linked to another Discord user
+    NameLinkedElsewhere,
+    /// The database could not be queried
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for LinkError {
+    fn from(e: sqlx::Error) -> Self {
+        LinkError::Database(e)
+    }
+}
+
+/// Generates a longer human-typable code, for codes that stay valid for a long time
+pub fn generate_long_code() -> String {
+    format!("{}-{}", generate_code(), generate_code())
+}
+
+/// Generates a short human-typable code
+pub fn generate_code() -> String {
+    Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(CODE_LENGTH)
+        .map(|byte| CODE_ALPHABET[*byte as usize % CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+/// Links a Minecraft account to a Discord user
+///
+/// The account is moved to `player_ip`, which becomes a trusted IP. Linking an
+/// account again to the same Discord user is allowed (e.g. to confirm a new IP).
+///
+/// # Errors
+/// Returns a [`LinkError`] if the account or its name is linked to another
+/// Discord user, or if the database fails
+pub async fn link_minecraft_user(
+    db: &SqlitePool,
+    minecraft_user_id: i64,
+    discord_user_id: i64,
+    player_ip: &str,
+) -> Result<(), LinkError> {
+    let record = sqlx::query!(
+        "SELECT discord_user_id, player_uuid, player_name FROM minecraft_users WHERE id = ?1",
+        minecraft_user_id
+    )
+    .fetch_one(db)
+    .await?;
+
+    // A linked account can only be confirmed by the same Discord user
+    if record
+        .discord_user_id
+        .is_some_and(|linked| linked != discord_user_id)
+    {
+        warn!(
+            "[link_minecraft_user] Minecraft user {} is already linked to another Discord user",
+            minecraft_user_id
+        );
+
+        return Err(LinkError::AccountLinkedElsewhere);
+    }
+
+    // Accounts without a UUID are identified by name, which must not be linked elsewhere
+    if record.player_uuid.is_none()
+        && sqlx::query!(
+            "SELECT id FROM minecraft_users WHERE player_name = ?1 AND discord_user_id != ?2",
+            record.player_name,
+            discord_user_id
+        )
+        .fetch_optional(db)
+        .await?
+        .is_some()
+    {
+        warn!(
+            "[link_minecraft_user] Player name `{}` is already linked to another Discord user",
+            record.player_name
+        );
+
+        return Err(LinkError::NameLinkedElsewhere);
+    }
+
+    // Insert Discord user if not exists
+    sqlx::query!(
+        "INSERT OR IGNORE INTO discord_users (id) VALUES (?1)",
+        discord_user_id
+    )
+    .execute(db)
+    .await?;
+
+    // Drop unlinked legacy rows left behind for the same name + IP
+    sqlx::query!(
+        "DELETE FROM minecraft_users
+        WHERE player_name = ?1 AND player_ip = ?2 AND player_uuid IS NULL
+            AND discord_user_id IS NULL AND id != ?3",
+        record.player_name,
+        player_ip,
+        minecraft_user_id
+    )
+    .execute(db)
+    .await?;
+
+    // Link Discord user to Minecraft user from the IP used to link it
+    let linked_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    sqlx::query!(
+        "UPDATE minecraft_users SET discord_user_id = ?1, player_ip = ?2, linked_at = ?3 WHERE id = ?4",
+        discord_user_id,
+        player_ip,
+        linked_at,
+        minecraft_user_id
+    )
+    .execute(db)
+    .await?;
+
+    // The IP used to link the account is trusted
+    trusted_ips::set_ip_status(db, minecraft_user_id, player_ip, TrustedIpStatus::Approved).await?;
+
+    info!(
+        "[link_minecraft_user] Linked Minecraft user {} to Discord user {}",
+        minecraft_user_id, discord_user_id
+    );
+
+    Ok(())
+}
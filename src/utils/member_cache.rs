@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use poise::serenity_prelude::{self as serenity, GuildId, Member, UserId};
+use tracing::debug;
+
+/// A cached guild member along with where it came from
+struct CachedMember {
+    member: Member,
+    /// When the member was fetched over REST, `None` for members fed by the gateway
+    fetched_at: Option<Instant>,
+}
+
+/// In-memory cache of guild members and their roles
+///
+/// The cache is populated from the gateway (guild create, member chunks and
+/// member additions) and kept fresh by member update/removal events, so those
+/// entries never expire. Members fetched over REST for guilds the gateway
+/// doesn't cover are treated as missing once older than the configured TTL.
+pub struct MemberCache {
+    members: RwLock<HashMap<(GuildId, UserId), CachedMember>>,
+    ttl: Duration,
+}
+
+impl MemberCache {
+    /// Creates an empty cache whose REST-fetched entries expire after `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            members: RwLock::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Inserts or replaces a single member received from the gateway
+    pub fn insert(&self, member: Member) {
+        let key = (member.guild_id, member.user.id);
+
+        self.members.write().unwrap().insert(
+            key,
+            CachedMember {
+                member,
+                fetched_at: None,
+            },
+        );
+    }
+
+    /// Inserts or replaces several members received from the gateway at once
+    pub fn insert_many(&self, members: impl IntoIterator<Item = Member>) {
+        let mut cache = self.members.write().unwrap();
+
+        for member in members {
+            cache.insert(
+                (member.guild_id, member.user.id),
+                CachedMember {
+                    member,
+                    fetched_at: None,
+                },
+            );
+        }
+    }
+
+    /// Applies a gateway member update to the cached member
+    ///
+    /// If the member is not cached yet, `new` (when provided by serenity) is
+    /// stored instead; otherwise the update is ignored and the next lookup
+    /// falls back to the REST API.
+    pub fn update(&self, event: &serenity::GuildMemberUpdateEvent, new: Option<&Member>) {
+        let key = (event.guild_id, event.user.id);
+        let mut cache = self.members.write().unwrap();
+
+        if let Some(cached) = cache.get_mut(&key) {
+            let member = &mut cached.member;
+            member.user = event.user.clone();
+            member.nick = event.nick.clone();
+            member.roles = event.roles.clone();
+            member.pending = event.pending;
+            member.premium_since = event.premium_since;
+            member.communication_disabled_until = event.communication_disabled_until;
+            cached.fetched_at = None;
+        } else if let Some(new) = new {
+            cache.insert(
+                key,
+                CachedMember {
+                    member: new.clone(),
+                    fetched_at: None,
+                },
+            );
+        }
+    }
+
+    /// Removes a member from the cache
+    pub fn remove(&self, guild_id: GuildId, user_id: UserId) {
+        self.members.write().unwrap().remove(&(guild_id, user_id));
+    }
+
+    /// Returns a cached member if present, ignoring REST-fetched members older than the TTL
+    pub fn get(&self, guild_id: GuildId, user_id: UserId) -> Option<Member> {
+        self.members
+            .read()
+            .unwrap()
+            .get(&(guild_id, user_id))
+            .filter(|cached| {
+                cached
+                    .fetched_at
+                    .is_none_or(|fetched_at| fetched_at.elapsed() <= self.ttl)
+            })
+            .map(|cached| cached.member.clone())
+    }
+
+    /// Returns the cached members of a guild matching `predicate`
+    ///
+    /// Only cached members are considered, uncached ones are left out.
+    pub fn find(&self, guild_id: GuildId, predicate: impl Fn(&Member) -> bool) -> Vec<UserId> {
+        self.members
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|((member_guild_id, _), cached)| {
+                *member_guild_id == guild_id
+                    && cached
+                        .fetched_at
+                        .is_none_or(|fetched_at| fetched_at.elapsed() <= self.ttl)
+                    && predicate(&cached.member)
+            })
+            .map(|((_, user_id), _)| *user_id)
+            .collect()
+    }
+
+    /// Returns a member from the cache, falling back to the REST API on a miss
+    ///
+    /// Members fetched over REST are stored in the cache for subsequent lookups,
+    /// until the TTL runs out.
+    ///
+    /// # Errors
+    /// Returns the serenity error if the member is not cached and the REST call fails
+    pub async fn get_or_fetch(
+        &self,
+        http: &serenity::Http,
+        guild_id: GuildId,
+        user_id: UserId,
+    ) -> Result<Member, serenity::Error> {
+        if let Some(member) = self.get(guild_id, user_id) {
+            debug!(
+                "[member_cache] Cache hit for user {} in guild {}",
+                user_id, guild_id
+            );
+
+            return Ok(member);
+        }
+
+        debug!(
+            "[member_cache] Cache miss for user {} in guild {}, fetching from Discord",
+            user_id, guild_id
+        );
+
+        let member = http.get_member(guild_id, user_id).await?;
+        self.members.write().unwrap().insert(
+            (guild_id, user_id),
+            CachedMember {
+                member: member.clone(),
+                fetched_at: Some(Instant::now()),
+            },
+        );
+
+        Ok(member)
+    }
+}
@@ -5,6 +5,108 @@ pub enum MinecraftServerType {
     Game = 1,
 }
 
+/// How unlinked players are asked to link their Discord account
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum RegistrationMode {
+    /// Discord OAuth2 link opened outside the game
+    #[name = "Discord OAuth"]
+    OAuth = 0,
+    /// Short code typed with `/minecraft verify` on Discord
+    #[name = "Verification code"]
+    Code = 1,
+}
+
+impl RegistrationMode {
+    /// Converts a stored integer back into a registration mode
+    pub fn from_i64(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Self::OAuth),
+            1 => Some(Self::Code),
+            _ => None,
+        }
+    }
+}
+
+/// How the roles assigned to a game server are matched against a member
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum RoleMatchMode {
+    /// The member needs at least one of the roles
+    #[name = "Any role"]
+    Any = 0,
+    /// The member needs every role
+    #[name = "All roles"]
+    All = 1,
+}
+
+impl RoleMatchMode {
+    /// Converts a stored integer back into a role match mode
+    pub fn from_i64(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Self::Any),
+            1 => Some(Self::All),
+            _ => None,
+        }
+    }
+}
+
+/// What a server does when Discord can't be reached to check a member
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum FailPolicy {
+    /// Deny everyone until Discord is back
+    #[name = "Fail closed"]
+    Closed = 0,
+    /// Allow members that were allowed recently (within the grace period)
+    #[name = "Last known good"]
+    LastKnownGood = 1,
+    /// Allow every linked player on lobby servers, deny game servers
+    #[name = "Fail open for lobby only"]
+    LobbyOnly = 2,
+}
+
+impl FailPolicy {
+    /// Converts a stored integer back into a fail policy
+    pub fn from_i64(value: i64) -> Option<Self> {
+        match value {
+            0 => Some(Self::Closed),
+            1 => Some(Self::LastKnownGood),
+            2 => Some(Self::LobbyOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Time window of playtime statistics
+#[derive(Debug, Clone, Copy, PartialEq, poise::ChoiceParameter)]
+pub enum PlaytimePeriod {
+    /// The last 24 hours
+    #[name = "Daily"]
+    Daily,
+    /// The last 7 days
+    #[name = "Weekly"]
+    Weekly,
+    /// Every recorded session
+    #[name = "All time"]
+    AllTime,
+}
+
+impl PlaytimePeriod {
+    /// Returns the start of the window (Unix timestamp in milliseconds)
+    pub fn start_ms(self, now_ms: i64) -> i64 {
+        match self {
+            Self::Daily => now_ms - 24 * 60 * 60 * 1000,
+            Self::Weekly => now_ms - 7 * 24 * 60 * 60 * 1000,
+            Self::AllTime => 0,
+        }
+    }
+}
+
+/// Status of an IP address in a player's trusted IP list
+pub enum TrustedIpStatus {
+    Pending = 0,
+    Approved = 1,
+    Denied = 2,
+}
+
 pub struct MinecraftMusicTrack {
     pub title: &'static str,
     pub author: &'static str,
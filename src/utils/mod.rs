@@ -1,8 +1,17 @@
+pub mod chat;
 pub mod checks;
 pub mod config;
 pub mod db;
 pub mod docker;
+pub mod duration;
 pub mod embed;
 pub mod influxdb;
+pub mod ip;
+pub mod linking;
+pub mod member_cache;
 pub mod minecraft;
+pub mod player_sessions;
+pub mod proxy_keys;
 pub mod snowflake;
+pub mod timeout_timers;
+pub mod trusted_ips;
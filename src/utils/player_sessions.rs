@@ -0,0 +1,201 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sqlx::SqlitePool;
+
+use crate::utils::minecraft::PlaytimePeriod;
+use crate::utils::snowflake;
+
+/// Returns the current time in milliseconds since the Unix epoch
+pub fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64
+}
+
+/// Starts a session of the player on a server of the proxy, ending their previous one
+///
+/// Players and servers unknown to Twig are ignored. The session never starts
+/// before the player's latest one on the proxy, so sessions don't overlap.
+pub async fn start(
+    db: &SqlitePool,
+    proxy_id: &str,
+    player_uuid: &str,
+    server_name: &str,
+    started_at: i64,
+) -> Result<(), sqlx::Error> {
+    let latest = sqlx::query!(
+        r#"SELECT MAX(COALESCE(player_sessions.ended_at, player_sessions.started_at)) AS "latest?: i64"
+        FROM player_sessions
+        JOIN minecraft_users ON player_sessions.minecraft_user_id = minecraft_users.id
+        JOIN minecraft_servers ON player_sessions.server_id = minecraft_servers.id
+        WHERE minecraft_users.player_uuid = ?1 AND minecraft_servers.proxy_id = ?2"#,
+        player_uuid,
+        proxy_id
+    )
+    .fetch_one(db)
+    .await?
+    .latest;
+    let started_at = latest.map_or(started_at, |latest| started_at.max(latest));
+
+    end(db, proxy_id, player_uuid, started_at).await?;
+
+    let id = snowflake::generate_id();
+    sqlx::query!(
+        "INSERT INTO player_sessions (id, minecraft_user_id, server_id, started_at, discord_guild_id)
+        SELECT ?1, minecraft_users.id, minecraft_servers.id, ?5, minecraft_proxies.discord_guild_id
+        FROM minecraft_users, minecraft_servers
+        JOIN minecraft_proxies ON minecraft_servers.proxy_id = minecraft_proxies.id
+        WHERE
+            minecraft_users.player_uuid = ?2 AND
+            minecraft_servers.proxy_id = ?3 AND
+            minecraft_servers.server_name = ?4",
+        id,
+        player_uuid,
+        proxy_id,
+        server_name,
+        started_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends the open session of the player on the servers of the proxy
+pub async fn end(
+    db: &SqlitePool,
+    proxy_id: &str,
+    player_uuid: &str,
+    ended_at: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE player_sessions SET ended_at = MAX(started_at, ?3)
+        WHERE
+            ended_at IS NULL AND
+            minecraft_user_id IN (SELECT id FROM minecraft_users WHERE player_uuid = ?2) AND
+            server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1)",
+        proxy_id,
+        player_uuid,
+        ended_at
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
+/// Ends every open session on the servers of the proxy (or of all proxies when `None`)
+pub async fn end_all(
+    db: &SqlitePool,
+    proxy_id: Option<&str>,
+    ended_at: i64,
+) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query!(
+        "UPDATE player_sessions SET ended_at = MAX(started_at, ?2)
+        WHERE
+            ended_at IS NULL AND
+            (?1 IS NULL OR server_id IN (SELECT id FROM minecraft_servers WHERE proxy_id = ?1))",
+        proxy_id,
+        ended_at
+    )
+    .execute(db)
+    .await?
+    .rows_affected())
+}
+
+/// Playtime of a Discord user on a server, in milliseconds
+pub struct ServerPlaytime {
+    pub server_name: String,
+    pub daily_ms: i64,
+    pub weekly_ms: i64,
+    pub total_ms: i64,
+}
+
+/// Returns the playtime of a Discord user on each server of the guild, most played first
+///
+/// Sessions still open count until now. Only the sessions played while the
+/// guild owned the proxy are counted.
+pub async fn playtime(
+    db: &SqlitePool,
+    guild_id: i64,
+    discord_user_id: i64,
+) -> Result<Vec<ServerPlaytime>, sqlx::Error> {
+    let now_ms = now_ms();
+    let daily_start = PlaytimePeriod::Daily.start_ms(now_ms);
+    let weekly_start = PlaytimePeriod::Weekly.start_ms(now_ms);
+
+    let records = sqlx::query!(
+        r#"SELECT
+            minecraft_servers.server_name,
+            SUM(MAX(0, COALESCE(player_sessions.ended_at, ?3) - MAX(player_sessions.started_at, ?4))) AS "daily_ms!: i64",
+            SUM(MAX(0, COALESCE(player_sessions.ended_at, ?3) - MAX(player_sessions.started_at, ?5))) AS "weekly_ms!: i64",
+            SUM(COALESCE(player_sessions.ended_at, ?3) - player_sessions.started_at) AS "total_ms!: i64"
+        FROM player_sessions
+        JOIN minecraft_users ON player_sessions.minecraft_user_id = minecraft_users.id
+        JOIN minecraft_servers ON player_sessions.server_id = minecraft_servers.id
+        WHERE player_sessions.discord_guild_id = ?1 AND minecraft_users.discord_user_id = ?2
+        GROUP BY minecraft_servers.id
+        ORDER BY "total_ms!: i64" DESC"#,
+        guild_id,
+        discord_user_id,
+        now_ms,
+        daily_start,
+        weekly_start
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| ServerPlaytime {
+            server_name: record.server_name,
+            daily_ms: record.daily_ms,
+            weekly_ms: record.weekly_ms,
+            total_ms: record.total_ms,
+        })
+        .collect())
+}
+
+/// Returns the Discord users of the guild with the most playtime in the period
+///
+/// Limited to `server_name` when given, as `(discord_user_id, playtime_ms)` pairs.
+pub async fn leaderboard(
+    db: &SqlitePool,
+    guild_id: i64,
+    server_name: Option<&str>,
+    period: PlaytimePeriod,
+    limit: i64,
+) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    let now_ms = now_ms();
+    let start_ms = period.start_ms(now_ms);
+
+    let records = sqlx::query!(
+        r#"SELECT
+            minecraft_users.discord_user_id AS "discord_user_id!",
+            SUM(COALESCE(player_sessions.ended_at, ?2) - MAX(player_sessions.started_at, ?3)) AS "playtime_ms!: i64"
+        FROM player_sessions
+        JOIN minecraft_users ON player_sessions.minecraft_user_id = minecraft_users.id
+        JOIN minecraft_servers ON player_sessions.server_id = minecraft_servers.id
+        WHERE
+            player_sessions.discord_guild_id = ?1 AND
+            minecraft_users.discord_user_id IS NOT NULL AND
+            COALESCE(player_sessions.ended_at, ?2) > ?3 AND
+            (?4 IS NULL OR minecraft_servers.server_name = ?4)
+        GROUP BY minecraft_users.discord_user_id
+        ORDER BY "playtime_ms!: i64" DESC
+        LIMIT ?5"#,
+        guild_id,
+        now_ms,
+        start_ms,
+        server_name,
+        limit
+    )
+    .fetch_all(db)
+    .await?;
+
+    Ok(records
+        .into_iter()
+        .map(|record| (record.discord_user_id, record.playtime_ms))
+        .collect())
+}
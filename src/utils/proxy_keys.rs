@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use tokio::sync::watch;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::utils::snowflake;
+
+/// Prefix of every proxy API key, to make leaked keys easy to recognize
+const KEY_PREFIX: &str = "twig_";
+
+/// In-memory index of proxy API key hashes
+///
+/// The gRPC interceptor runs synchronously, so keys are mirrored from the
+/// `minecraft_proxy_keys` table and kept in sync by the key commands.
+pub struct ProxyKeyStore {
+    /// Key hash -> proxy ID
+    keys: RwLock<HashMap<String, String>>,
+    /// Notified whenever a key is rotated or revoked, so open streams can be closed
+    revocations: watch::Sender<()>,
+}
+
+impl ProxyKeyStore {
+    /// Loads every stored key hash from the database
+    ///
+    /// # Errors
+    /// Returns the sqlx error if the keys cannot be queried
+    pub async fn load(db: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let records = sqlx::query!("SELECT proxy_id, key_hash FROM minecraft_proxy_keys")
+            .fetch_all(db)
+            .await?;
+
+        info!("[ProxyKeyStore::load] Loaded {} proxy keys", records.len());
+
+        Ok(Self {
+            keys: RwLock::new(
+                records
+                    .into_iter()
+                    .map(|record| (record.key_hash, record.proxy_id))
+                    .collect(),
+            ),
+            revocations: watch::Sender::new(()),
+        })
+    }
+
+    /// Returns the proxy ID the API key hash belongs to
+    pub fn authenticate(&self, key_hash: &str) -> Option<String> {
+        self.keys.read().unwrap().get(key_hash).cloned()
+    }
+
+    /// Returns whether the key hash is still the current key of the proxy
+    pub fn is_current(&self, proxy_id: &str, key_hash: &str) -> bool {
+        self.keys
+            .read()
+            .unwrap()
+            .get(key_hash)
+            .is_some_and(|owner| owner == proxy_id)
+    }
+
+    /// Returns whether the proxy currently has an API key
+    pub fn has_key(&self, proxy_id: &str) -> bool {
+        self.keys
+            .read()
+            .unwrap()
+            .values()
+            .any(|owner| owner == proxy_id)
+    }
+
+    /// Returns a receiver notified whenever a key is rotated or revoked
+    pub fn subscribe_revocations(&self) -> watch::Receiver<()> {
+        self.revocations.subscribe()
+    }
+
+    /// Replaces the key hash of a proxy
+    fn set(&self, proxy_id: &str, key_hash: String) {
+        let mut keys = self.keys.write().unwrap();
+        keys.retain(|_, owner| owner != proxy_id);
+        keys.insert(key_hash, proxy_id.to_string());
+        drop(keys);
+
+        // Streams opened with the previous key must be closed
+        self.revocations.send_replace(());
+    }
+
+    /// Forgets the key of a proxy
+    fn remove(&self, proxy_id: &str) {
+        self.keys
+            .write()
+            .unwrap()
+            .retain(|_, owner| owner != proxy_id);
+
+        self.revocations.send_replace(());
+    }
+}
+
+/// Generates a new random API key
+fn generate_key() -> String {
+    format!(
+        "{}{}{}",
+        KEY_PREFIX,
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Returns the hex-encoded SHA-256 of an API key
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Issues a new API key for a proxy, replacing its previous key
+///
+/// Only the hash is stored, the returned key cannot be recovered later.
+///
+/// # Errors
+/// Returns the sqlx error if the key cannot be stored
+pub async fn issue_key(
+    db: &SqlitePool,
+    store: &ProxyKeyStore,
+    proxy_id: &str,
+) -> Result<String, sqlx::Error> {
+    let id = snowflake::generate_id();
+    let key = generate_key();
+    let key_hash = hash_key(&key);
+
+    // The proxy may not have registered yet
+    sqlx::query!(
+        "INSERT OR IGNORE INTO minecraft_proxies (id) VALUES (?1)",
+        proxy_id
+    )
+    .execute(db)
+    .await?;
+
+    sqlx::query!(
+        "INSERT OR REPLACE INTO minecraft_proxy_keys (id, proxy_id, key_hash) VALUES (?1, ?2, ?3)",
+        id,
+        proxy_id,
+        key_hash
+    )
+    .execute(db)
+    .await?;
+
+    store.set(proxy_id, key_hash);
+
+    info!("[issue_key] Issued a new API key for proxy `{}`", proxy_id);
+
+    Ok(key)
+}
+
+/// Revokes the API key of a proxy
+///
+/// Returns whether the proxy had a key.
+///
+/// # Errors
+/// Returns the sqlx error if the key cannot be deleted
+pub async fn revoke_key(
+    db: &SqlitePool,
+    store: &ProxyKeyStore,
+    proxy_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        "DELETE FROM minecraft_proxy_keys WHERE proxy_id = ?1",
+        proxy_id
+    )
+    .execute(db)
+    .await?;
+
+    store.remove(proxy_id);
+
+    info!("[revoke_key] Revoked the API key of proxy `{}`", proxy_id);
+
+    Ok(result.rows_affected() > 0)
+}
@@ -27,11 +27,17 @@ pub fn generate_id() -> i64 {
     g.generate()
 }
 
-fn snowflake_to_timestamp(snowflake: i64) -> u64 {
+/// Returns the creation time of a snowflake in milliseconds since the Unix epoch
+pub fn snowflake_to_timestamp(snowflake: i64) -> u64 {
     let snowflake_u64 = snowflake as u64;
     snowflake_u64 >> 22
 }
 
+/// Returns the smallest snowflake created at `timestamp_ms` (milliseconds since the Unix epoch)
+pub fn timestamp_to_snowflake(timestamp_ms: u64) -> i64 {
+    (timestamp_ms << 22) as i64
+}
+
 pub fn is_snowflake_recent(snowflake: i64, max_age_ms: u64) -> bool {
     let timestamp_ms = snowflake_to_timestamp(snowflake);
     let now_ms = SystemTime::now()
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use poise::serenity_prelude::{GuildId, Timestamp, UserId};
+use tokio::task::JoinHandle;
+
+/// Pending re-checks of members whose Discord timeout runs out, at most one per member
+#[derive(Default)]
+pub struct TimeoutTimers {
+    timers: Mutex<HashMap<(GuildId, UserId), (Timestamp, JoinHandle<()>)>>,
+}
+
+impl TimeoutTimers {
+    /// Returns when the member's pending re-check fires, if any
+    pub fn ends_at(&self, guild_id: GuildId, user_id: UserId) -> Option<Timestamp> {
+        self.timers
+            .lock()
+            .unwrap()
+            .get(&(guild_id, user_id))
+            .map(|(until, _)| *until)
+    }
+
+    /// Replaces the member's pending re-check, aborting the previous one
+    pub fn set(&self, guild_id: GuildId, user_id: UserId, until: Timestamp, task: JoinHandle<()>) {
+        if let Some((_, previous)) = self
+            .timers
+            .lock()
+            .unwrap()
+            .insert((guild_id, user_id), (until, task))
+        {
+            previous.abort();
+        }
+    }
+
+    /// Aborts the member's pending re-check, e.g. when the timeout is lifted early
+    pub fn cancel(&self, guild_id: GuildId, user_id: UserId) {
+        if let Some((_, task)) = self.timers.lock().unwrap().remove(&(guild_id, user_id)) {
+            task.abort();
+        }
+    }
+
+    /// Forgets a re-check that fired, unless it was replaced in the meantime
+    pub fn finish(&self, guild_id: GuildId, user_id: UserId, until: Timestamp) {
+        let mut timers = self.timers.lock().unwrap();
+
+        if timers
+            .get(&(guild_id, user_id))
+            .is_some_and(|(scheduled, _)| *scheduled == until)
+        {
+            timers.remove(&(guild_id, user_id));
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::net::IpAddr;
+
+use sqlx::SqlitePool;
+
+use crate::utils::minecraft::TrustedIpStatus;
+use crate::utils::{ip, snowflake};
+
+/// How long an IP approval request stays valid
+pub const APPROVAL_EXPIRY_MS: u64 = 5 * 60 * 1000;
+
+/// How much an IP address is trusted for a linked Minecraft account
+pub enum IpTrust {
+    /// The IP is the linked one or was approved by the Discord user
+    Trusted,
+    /// An approval request for the IP is waiting for the Discord user
+    Pending,
+    /// The Discord user denied the IP
+    Denied,
+    /// The IP was never seen for this account
+    Unknown,
+}
+
+/// Evaluates an IP against the account's last IP and its trusted IP list
+///
+/// Approved entries win over denied ones, which win over pending ones.
+/// Pending entries older than [`APPROVAL_EXPIRY_MS`] are ignored.
+pub async fn get_ip_trust(
+    db: &SqlitePool,
+    minecraft_user_id: i64,
+    last_ip: &str,
+    player_addr: IpAddr,
+) -> IpTrust {
+    if ip::parse(last_ip).is_some_and(|stored| ip::same_network(stored, player_addr)) {
+        return IpTrust::Trusted;
+    }
+
+    let records = sqlx::query!(
+        "SELECT id, player_ip, status FROM minecraft_trusted_ips WHERE minecraft_user_id = ?1",
+        minecraft_user_id
+    )
+    .fetch_all(db)
+    .await
+    .unwrap_or_default();
+
+    let mut trust = IpTrust::Unknown;
+
+    for record in records {
+        if !ip::parse(&record.player_ip).is_some_and(|stored| ip::same_network(stored, player_addr))
+        {
+            continue;
+        }
+
+        if record.status == TrustedIpStatus::Approved as i64 {
+            return IpTrust::Trusted;
+        } else if record.status == TrustedIpStatus::Denied as i64 {
+            trust = IpTrust::Denied;
+        } else if matches!(trust, IpTrust::Unknown)
+            && snowflake::is_snowflake_recent(record.id, APPROVAL_EXPIRY_MS)
+        {
+            trust = IpTrust::Pending;
+        }
+    }
+
+    trust
+}
+
+/// Records an IP for an account with the given status, replacing any previous entry
+///
+/// # Returns
+/// The snowflake ID of the trusted IP entry
+pub async fn set_ip_status(
+    db: &SqlitePool,
+    minecraft_user_id: i64,
+    player_ip: &str,
+    status: TrustedIpStatus,
+) -> Result<i64, sqlx::Error> {
+    let id = snowflake::generate_id();
+    let status = status as i64;
+
+    sqlx::query!(
+        "INSERT OR REPLACE INTO minecraft_trusted_ips (id, minecraft_user_id, player_ip, status)
+        VALUES (?1, ?2, ?3, ?4)",
+        id,
+        minecraft_user_id,
+        player_ip,
+        status
+    )
+    .execute(db)
+    .await?;
+
+    Ok(id)
+}